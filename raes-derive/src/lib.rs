@@ -0,0 +1,494 @@
+use proc_macro::TokenStream;
+use proc_macro2::{Ident, Span};
+use quote::{format_ident, quote, ToTokens};
+use syn::{
+    parse_macro_input, Data, DeriveInput, Error, Fields, GenericArgument, Index, PathArguments,
+    Type,
+};
+
+enum ContField<'a> {
+    Entities(&'a Ident),
+    Row(&'a Ident, &'a Type),
+    SparseRow(&'a Ident, &'a Type),
+    TagRow(&'a Ident),
+    Other,
+}
+
+/// Implements `raes::base::Cont` and `raes::base::ContSpawn` for a struct holding one
+/// `ContEntities<Self>` and any number of `Row`s, `SparseRow`s and `TagRow`s.
+///
+/// Also generates:
+/// - `spawn_with` and `try_spawn_with`, taking the initial value of every `Row` in field order.
+/// - `despawn` and `try_despawn`, which reset every `Row` slot of the entity, remove its
+///   `SparseRow` values and clear its `TagRow` bits.
+/// - `<row>` and `<row>_mut` accessors for every `Row`.
+/// - A `Flushable` implementation that flushes the entity table and every `Row`.
+/// - With `#[cont(snapshot)]`, a `Snapshot` implementation over the entity table and every row,
+///   in field order, which requires every `SparseRow` value to be `Clone`.
+///
+/// ```ignore
+/// #[derive(Cont)]
+/// #[cont(snapshot)]
+/// struct Enemies {
+///     entities: ContEntities<Self>,
+///     health: Row<f32>,
+///     on_fire: SparseRow<f32>,
+/// }
+///
+/// let enemy = enemies.spawn_with(100.0);
+/// *enemies.health_mut(enemy) -= 10.0;
+/// ```
+#[proc_macro_derive(Cont, attributes(cont))]
+pub fn derive_cont(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_cont(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+struct ContFields<'a> {
+    entities: &'a Ident,
+    rows: Vec<(&'a Ident, &'a Type)>,
+    sparse_rows: Vec<(&'a Ident, &'a Type)>,
+    tag_rows: Vec<&'a Ident>,
+    /// Every field known to `#[derive(Cont)]` with its full type, in declaration order.
+    known: Vec<(&'a Ident, &'a Type)>,
+}
+
+fn parse_cont_fields(input: &DeriveInput) -> syn::Result<ContFields<'_>> {
+    let name = &input.ident;
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => Err(Error::new_spanned(
+                name,
+                "`#[derive(Cont)]` requires a struct with named fields.",
+            ))?,
+        },
+        _ => Err(Error::new_spanned(
+            name,
+            "`#[derive(Cont)]` can only be used on structs.",
+        ))?,
+    };
+
+    let mut entities = None;
+    let mut rows = vec![];
+    let mut sparse_rows = vec![];
+    let mut tag_rows = vec![];
+    let mut known = vec![];
+    for field in fields {
+        let ident = field.ident.as_ref().unwrap();
+        let class = classify_field(ident, &field.ty);
+        if !matches!(class, ContField::Other) {
+            known.push((ident, &field.ty));
+        }
+        match class {
+            ContField::Entities(ident) => {
+                if entities.replace(ident).is_some() {
+                    Err(Error::new_spanned(
+                        field,
+                        "`#[derive(Cont)]` found more than one `ContEntities` field.",
+                    ))?
+                }
+            }
+            ContField::Row(ident, ty) => rows.push((ident, ty)),
+            ContField::SparseRow(ident, ty) => sparse_rows.push((ident, ty)),
+            ContField::TagRow(ident) => tag_rows.push(ident),
+            ContField::Other => {}
+        }
+    }
+    let entities = entities.ok_or_else(|| {
+        Error::new_spanned(
+            name,
+            "`#[derive(Cont)]` requires a `ContEntities<Self>` field.",
+        )
+    })?;
+
+    Ok(ContFields {
+        entities,
+        rows,
+        sparse_rows,
+        tag_rows,
+        known,
+    })
+}
+
+/// Implements `raes::asset::ContPrefab` for a struct that also derives `Cont`, so that
+/// `raes::asset::Prefab`s can write its rows by field name.
+///
+/// `Row` and `SparseRow` values are deserialized from the row's type, and `TagRow`s from a `bool`.
+///
+/// ```ignore
+/// #[derive(Cont, ContPrefab)]
+/// struct Enemies {
+///     entities: ContEntities<Self>,
+///     health: Row<f32>,
+///     boss: TagRow,
+/// }
+/// ```
+#[proc_macro_derive(ContPrefab)]
+pub fn derive_cont_prefab(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_cont_prefab(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand_cont_prefab(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let ContFields {
+        rows,
+        sparse_rows,
+        tag_rows,
+        ..
+    } = parse_cont_fields(input)?;
+
+    let row_idents = rows.iter().map(|(ident, _)| *ident).collect::<Vec<_>>();
+    let row_names = row_idents.iter().map(|ident| ident.to_string());
+    let row_tys = rows.iter().map(|(_, ty)| *ty);
+    let sparse_idents = sparse_rows
+        .iter()
+        .map(|(ident, _)| *ident)
+        .collect::<Vec<_>>();
+    let sparse_names = sparse_idents.iter().map(|ident| ident.to_string());
+    let sparse_tys = sparse_rows.iter().map(|(_, ty)| *ty);
+    let tag_names = tag_rows.iter().map(|ident| ident.to_string());
+    let entity = Ident::new("entity", Span::mixed_site());
+
+    Ok(quote! {
+        impl #impl_generics ::raes::asset::ContPrefab for #name #ty_generics #where_clause {
+            fn write_prefab_row(
+                &mut self,
+                #entity: ::raes::base::Entity<Self>,
+                row: &str,
+                value: &::raes::asset::PrefabValue,
+            ) -> Result<(), ::raes::asset::PrefabError> {
+                match row {
+                    #(
+                        #row_names => {
+                            *self.#row_idents.get_mut(#entity) =
+                                ::raes::asset::parse_prefab_value::<#row_tys>(row, value)?;
+                        }
+                    )*
+                    #(
+                        #sparse_names => {
+                            self.#sparse_idents.insert(
+                                #entity,
+                                ::raes::asset::parse_prefab_value::<#sparse_tys>(row, value)?,
+                            );
+                        }
+                    )*
+                    #(
+                        #tag_names => {
+                            if ::raes::asset::parse_prefab_value::<bool>(row, value)? {
+                                self.#tag_rows.set(#entity);
+                            } else {
+                                self.#tag_rows.clear(#entity);
+                            }
+                        }
+                    )*
+                    _ => Err(::raes::asset::PrefabError::UnknownRow(row.to_owned()))?,
+                }
+                Ok(())
+            }
+        }
+    })
+}
+
+/// Implements `raes::base::Inject` for a scene struct.
+///
+/// Fields marked `#[inject(icebox)]` must be `Manual<Box<P>>` and are taken out of the `IceBox`.
+/// Fields marked `#[inject(edge)]` must be `Manual<Arc<E>>` and are subscribed to through `Edges`.
+/// Every missing dependency is reported in one `InjectError::Missing`, and fields that were
+/// already injected in one `InjectError::AlreadyInjected`. The icebox holds one value of each
+/// type, so two `#[inject(icebox)]` fields of the same type are rejected.
+/// Scenes with nothing to inject derive it without marking any field.
+///
+/// ```ignore
+/// #[derive(Default, Serialize, Deserialize, Inject)]
+/// struct HelloScene {
+///     #[serde(skip)]
+///     #[inject(icebox)]
+///     surface: Manual<Box<SurfaceCont>>,
+/// }
+/// ```
+#[proc_macro_derive(Inject, attributes(inject))]
+pub fn derive_inject(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_inject(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand_inject(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let fields = match &input.data {
+        Data::Struct(data) => data.fields.iter().collect::<Vec<_>>(),
+        _ => Err(Error::new_spanned(
+            name,
+            "`#[derive(Inject)]` can only be used on structs.",
+        ))?,
+    };
+
+    let mut icebox_fields = vec![];
+    let mut icebox_types = vec![];
+    let mut edge_fields = vec![];
+    for field in fields {
+        for attr in field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("inject"))
+        {
+            let ident = field
+                .ident
+                .as_ref()
+                .ok_or_else(|| Error::new_spanned(field, "`#[inject]` requires a named field."))?;
+            let source: Ident = attr.parse_args()?;
+            match source.to_string().as_str() {
+                "icebox" => {
+                    let ty = field.ty.to_token_stream().to_string().replace(' ', "");
+                    if let Some(index) = icebox_types.iter().position(|other| *other == ty) {
+                        let other: &Ident = icebox_fields[index];
+                        Err(Error::new_spanned(
+                            field,
+                            format!(
+                                "`{}` and `{}` both take `{}` out of the icebox, which holds only one.",
+                                other, ident, ty
+                            ),
+                        ))?
+                    }
+                    icebox_fields.push(ident);
+                    icebox_types.push(ty);
+                }
+                "edge" => edge_fields.push(ident),
+                _ => Err(Error::new_spanned(
+                    source,
+                    "Expected `#[inject(icebox)]` or `#[inject(edge)]`.",
+                ))?,
+            }
+        }
+    }
+    let icebox_names = icebox_fields.iter().map(|ident| ident.to_string());
+    let edge_names = edge_fields.iter().map(|ident| ident.to_string());
+
+    Ok(quote! {
+        impl #impl_generics ::raes::base::Inject for #name #ty_generics #where_clause {
+            fn inject(
+                &mut self,
+                icebox: &mut ::raes::base::IceBox,
+                edges: &::raes::base::Edges,
+            ) -> Result<(), ::raes::base::InjectError> {
+                let mut check = ::raes::base::InjectCheck::default();
+                #(::raes::base::check_icebox(&self.#icebox_fields, icebox, #icebox_names, &mut check);)*
+                #(::raes::base::check_edge(&self.#edge_fields, edges, #edge_names, &mut check);)*
+                check.finish(::std::any::type_name::<Self>())?;
+
+                #(::raes::base::inject_icebox(&mut self.#icebox_fields, icebox)?;)*
+                #(::raes::base::inject_edge(&mut self.#edge_fields, edges)?;)*
+                Ok(())
+            }
+        }
+    })
+}
+
+/// Whether the struct is marked `#[cont(snapshot)]`.
+fn parse_cont_snapshot(input: &DeriveInput) -> syn::Result<bool> {
+    let mut snapshot = false;
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("cont"))
+    {
+        let option: Ident = attr.parse_args()?;
+        if option != "snapshot" {
+            Err(Error::new_spanned(option, "Expected `#[cont(snapshot)]`."))?
+        }
+        snapshot = true;
+    }
+    Ok(snapshot)
+}
+
+fn expand_cont(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let ContFields {
+        entities,
+        rows,
+        sparse_rows,
+        tag_rows,
+        known: snapshot_fields,
+    } = parse_cont_fields(input)?;
+    let sparse_rows = sparse_rows
+        .iter()
+        .map(|(ident, _)| *ident)
+        .collect::<Vec<_>>();
+
+    let row_idents = rows.iter().map(|(ident, _)| *ident).collect::<Vec<_>>();
+    let row_tys = rows.iter().map(|(_, ty)| *ty).collect::<Vec<_>>();
+    let row_mut_idents = row_idents
+        .iter()
+        .map(|ident| format_ident!("{}_mut", ident))
+        .collect::<Vec<_>>();
+    //  Mixed site, so that rows named like the generated locals cannot shadow them.
+    let entity = Ident::new("entity", Span::mixed_site());
+    let init = Ident::new("init", Span::mixed_site());
+    let written = Ident::new("written", Span::mixed_site());
+    let snapshot_idents = snapshot_fields
+        .iter()
+        .map(|(ident, _)| *ident)
+        .collect::<Vec<_>>();
+    let snapshot_tys = snapshot_fields
+        .iter()
+        .map(|(_, ty)| *ty)
+        .collect::<Vec<_>>();
+    let snapshot_indices = (0..snapshot_fields.len()).map(Index::from);
+    let restore_indices = snapshot_indices.clone();
+    let snapshot_impl = parse_cont_snapshot(input)?.then(|| {
+        quote! {
+            impl #impl_generics ::raes::base::Snapshot for #name #ty_generics #where_clause {
+                type State = (#(<#snapshot_tys as ::raes::base::Snapshot>::State,)*);
+
+                fn snapshot(&self) -> Self::State {
+                    (#(::raes::base::Snapshot::snapshot(&self.#snapshot_idents),)*)
+                }
+
+                fn snapshot_into(&self, state: &mut Self::State) {
+                    #(::raes::base::Snapshot::snapshot_into(&self.#snapshot_idents, &mut state.#snapshot_indices);)*
+                }
+
+                fn restore(&mut self, state: &Self::State) {
+                    #(::raes::base::Snapshot::restore(&mut self.#snapshot_idents, &state.#restore_indices);)*
+                }
+            }
+        }
+    });
+
+    Ok(quote! {
+        impl #impl_generics ::raes::base::Cont for #name #ty_generics #where_clause {}
+
+        impl #impl_generics ::raes::base::ContSpawn for #name #ty_generics #where_clause {
+            type Init = (#(#row_tys,)*);
+
+            fn try_spawn_init(
+                &mut self,
+                #init: Self::Init,
+            ) -> Result<::raes::base::Entity<Self>, ::raes::base::ContError> {
+                let (#(#row_idents,)*) = #init;
+                self.try_spawn_with(#(#row_idents),*)
+            }
+
+            fn try_spawn_empty(
+                &mut self,
+            ) -> Result<::raes::base::Entity<Self>, ::raes::base::ContError> {
+                let #entity = self.#entities.try_spawn()?;
+                #(self.#row_idents.grow_to_fit(&self.#entities);)*
+                //  Rows do not check generations, so clear anything left behind in the slot.
+                #(self.#row_idents.reset(#entity);)*
+                #(self.#sparse_rows.reset(#entity);)*
+                #(self.#tag_rows.clear(#entity);)*
+                Ok(#entity)
+            }
+
+            fn despawn_reset(&mut self, #entity: ::raes::base::Entity<Self>) -> Option<()> {
+                self.despawn(#entity)
+            }
+
+            fn has_entity(&self, #entity: &::raes::base::Entity<Self>) -> bool {
+                self.#entities.has_entity(#entity)
+            }
+        }
+
+        impl #impl_generics ::raes::base::Flushable for #name #ty_generics #where_clause {
+            fn flush(&mut self) {
+                ::raes::base::Flushable::flush(&mut self.#entities);
+                #(::raes::base::Flushable::flush(&mut self.#row_idents);)*
+            }
+        }
+
+        #snapshot_impl
+
+        impl #impl_generics #name #ty_generics #where_clause {
+            pub fn spawn_with(&mut self, #(#row_idents: #row_tys),*) -> ::raes::base::Entity<Self> {
+                self.try_spawn_with(#(#row_idents),*)
+                    .unwrap_or_else(|e| panic!("On spawn: {}", e))
+            }
+
+            pub fn try_spawn_with(
+                &mut self,
+                #(#row_idents: #row_tys),*
+            ) -> Result<::raes::base::Entity<Self>, ::raes::base::ContError> {
+                let #entity = self.#entities.try_spawn()?;
+                #(self.#row_idents.grow_to_fit(&self.#entities);)*
+                let #written = (|| {
+                    #(*self.#row_idents.try_get_mut(#entity)? = #row_idents;)*
+                    Ok(())
+                })();
+                if let Err(e) = #written {
+                    self.despawn(#entity);
+                    Err(e)?
+                }
+                Ok(#entity)
+            }
+
+            pub fn despawn(&mut self, #entity: ::raes::base::Entity<Self>) -> Option<()> {
+                self.try_despawn(#entity).ok()
+            }
+
+            pub fn try_despawn(
+                &mut self,
+                #entity: ::raes::base::Entity<Self>,
+            ) -> Result<(), ::raes::base::ContError> {
+                self.#entities.try_despawn(#entity)?;
+                #(self.#row_idents.reset(#entity);)*
+                #(self.#sparse_rows.remove(#entity);)*
+                #(self.#tag_rows.clear(#entity);)*
+                Ok(())
+            }
+
+            #(
+                pub fn #row_idents(&self, #entity: ::raes::base::Entity<Self>) -> &#row_tys {
+                    self.#row_idents.get(#entity)
+                }
+
+                pub fn #row_mut_idents(&mut self, #entity: ::raes::base::Entity<Self>) -> &mut #row_tys {
+                    self.#row_idents.get_mut(#entity)
+                }
+            )*
+        }
+    })
+}
+
+fn classify_field<'a>(ident: &'a Ident, ty: &'a Type) -> ContField<'a> {
+    let Type::Path(path) = ty else {
+        return ContField::Other;
+    };
+    let Some(segment) = path.path.segments.last() else {
+        return ContField::Other;
+    };
+
+    match segment.ident.to_string().as_str() {
+        "ContEntities" => ContField::Entities(ident),
+        "TagRow" => ContField::TagRow(ident),
+        "Row" => match first_type_argument(&segment.arguments) {
+            Some(row_ty) => ContField::Row(ident, row_ty),
+            None => ContField::Other,
+        },
+        "SparseRow" => match first_type_argument(&segment.arguments) {
+            Some(row_ty) => ContField::SparseRow(ident, row_ty),
+            None => ContField::Other,
+        },
+        _ => ContField::Other,
+    }
+}
+
+fn first_type_argument(arguments: &PathArguments) -> Option<&Type> {
+    let PathArguments::AngleBracketed(args) = arguments else {
+        return None;
+    };
+    match args.args.first() {
+        Some(GenericArgument::Type(ty)) => Some(ty),
+        _ => None,
+    }
+}
@@ -7,7 +7,7 @@ fn main() -> anyhow::Result<()> {
     engine.add_scene::<HelloScene>(&["hello.ron"]);
 
     let mut current_scene = engine.get_first_scene();
-    let mut current_icebox = init_icebox();
+    let mut current_icebox = init_icebox(&mut engine)?;
     while let Some((next_scene, next_icebox)) = engine.run_scene(&current_scene, current_icebox)? {
         current_scene = next_scene;
         current_icebox = next_icebox;
@@ -16,33 +16,36 @@ fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
-fn init_icebox() -> IceBox {
+fn init_icebox(engine: &mut Engine) -> anyhow::Result<IceBox> {
     let mut icebox = IceBox::default();
-    icebox.put(Box::new(SurfaceCont::new()));
-    icebox
+    let mut surface = SurfaceCont::new();
+    surface.publish_edges(engine)?;
+    icebox.put(Box::new(surface));
+    engine
+        .edges_mut()
+        .publish(RwLock::new(AssetLoaderEdgeData::new()))?;
+    Ok(icebox)
 }
 
-#[derive(Default, Serialize, Deserialize)]
+#[derive(Default, Serialize, Deserialize, Inject)]
 pub struct HelloScene {
     #[serde(skip)]
+    #[inject(icebox)]
     surface: Manual<Box<SurfaceCont>>,
     #[serde(skip)]
-    surface_edge: Manual<CopySwap<SurfaceEdgeData>>,
+    #[inject(edge)]
+    surface_edge: Manual<Arc<TripleBuffer<SurfaceEdgeData>>>,
     #[serde(skip)]
-    asset_edge: Manual<RwLock<AssetLoaderEdgeData>>,
+    #[inject(edge)]
+    asset_edge: Manual<Arc<RwLock<AssetLoaderEdgeData>>>,
 }
 
 impl Scene for HelloScene {
-    fn run(&mut self, mut icebox: IceBox) -> anyhow::Result<SceneExit> {
+    fn run(&mut self, _icebox: IceBox, _frames: Frames) -> anyhow::Result<SceneExit> {
         let rt = tokio::runtime::Runtime::new().unwrap();
         rt.block_on(async {
-            self.surface.init(icebox.take().unwrap());
-            self.surface_edge
-                .init(CopySwap::new(SurfaceEdgeData::new()));
-            self.asset_edge
-                .init(RwLock::new(AssetLoaderEdgeData::new()));
-
-            let mut text = self.asset_edge.get_mut().load("./text.txt").await?;
+            let text = self.asset_edge.read().load("./text.txt");
+            let mut text = text.await?;
 
             tokio::select! {
                 text = text.get_latest() => {
@@ -1,11 +1,15 @@
 use crate::base::*;
-use std::collections::HashMap;
+use std::{collections::HashMap, future::Future};
 use tokio::sync::watch;
 
 mod fs_platform;
+mod prefab;
 
 use fs_platform::{fs_platform_load, fs_platform_watch};
 
+pub use prefab::{parse_prefab_value, ContPrefab, Prefab, PrefabAsset, PrefabError, PrefabValue};
+pub use raes_derive::ContPrefab;
+
 pub struct LoadedData(watch::Receiver<Arc<[u8]>>);
 
 impl LoadedData {
@@ -13,6 +17,14 @@ impl LoadedData {
         self.0.changed().await.unwrap();
         self.0.borrow_and_update().clone()
     }
+
+    /// Returns the data if it changed since it was last read, without waiting.
+    pub fn try_get_latest(&mut self) -> Option<Arc<[u8]>> {
+        self.0
+            .has_changed()
+            .unwrap_or(false)
+            .then(|| self.0.borrow_and_update().clone())
+    }
 }
 
 struct Asset {
@@ -24,49 +36,64 @@ struct AssetWatcher {
 }
 
 pub struct AssetLoaderEdgeData {
-    datas: HashMap<String, Asset>,
+    datas: Arc<Mutex<HashMap<String, Asset>>>,
     watcher_data: Arc<Mutex<AssetWatcher>>,
 }
 
 impl AssetLoaderEdgeData {
+    /// Spawns a thread watching for file changes, so there is deliberately no `Default`.
+    #[allow(clippy::new_without_default)]
     pub fn new() -> Self {
         let watcher_data = Arc::new(Mutex::new(AssetWatcher {
             senders: HashMap::new(),
         }));
         let thread_watcher_data = Arc::clone(&watcher_data);
-        tokio::task::spawn(async {
-            let watcher_data = thread_watcher_data;
-            let _ = fs_platform_watch(watcher_data);
+        //  The watcher blocks on file events, so it gets its own thread rather than a runtime
+        //  worker, which would also keep the runtime from shutting down.
+        std::thread::spawn(move || {
+            let _ = fs_platform_watch(thread_watcher_data);
         });
 
         Self {
-            datas: HashMap::new(),
+            datas: Arc::new(Mutex::new(HashMap::new())),
             watcher_data,
         }
     }
 
-    pub async fn load(&mut self, path: &str) -> anyhow::Result<LoadedData> {
-        //  TODO: Find a cross platform method of resolving file paths for better caching.
-        let recv = if let Some(asset) = self.datas.get(path) {
-            asset.recv.clone()
-        } else {
-            let data_bytes = fs_platform_load(path).await?;
+    /// The returned future does not borrow the loader, so a lock around it is not held while
+    /// the file is read.
+    pub fn load(&self, path: &str) -> impl Future<Output = anyhow::Result<LoadedData>> + 'static {
+        let path = String::from(path);
+        let datas = Arc::clone(&self.datas);
+        let watcher_data = Arc::clone(&self.watcher_data);
+        async move {
+            //  TODO: Find a cross platform method of resolving file paths for better caching.
+            if let Some(asset) = datas.lock().get(&path) {
+                return Ok(LoadedData(asset.recv.clone()));
+            }
+
+            let data_bytes = fs_platform_load(&path).await?;
             let data: Arc<[u8]> = Arc::from(data_bytes.as_slice());
 
+            let mut datas = datas.lock();
+            //  Another load of the same path may have finished first.
+            if let Some(asset) = datas.get(&path) {
+                return Ok(LoadedData(asset.recv.clone()));
+            }
             let (send, recv) = watch::channel(data.clone());
-            let mut watcher_data = self.watcher_data.lock();
+            let mut watcher_data = watcher_data.lock();
             //  First send to signal changed initially.
             send.send(data)?;
             watcher_data
                 .senders
-                .entry(String::from(path))
+                .entry(path.clone())
                 .or_insert(vec![])
                 .push(send);
             let ret_recv = recv.clone();
-            let asset = Asset { recv };
-            self.datas.insert(String::from(path), asset);
-            ret_recv
-        };
-        Ok(LoadedData(recv))
+            datas.insert(path, Asset { recv });
+            Ok(LoadedData(ret_recv))
+        }
     }
 }
+
+impl Flushable for AssetLoaderEdgeData {}
@@ -0,0 +1,163 @@
+use super::*;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::BTreeMap;
+use thiserror::Error;
+
+/// The value of one row in a prefab file.
+pub type PrefabValue = ron::Value;
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum PrefabError {
+    #[error("Prefab parse error: `{0}`.")]
+    Parse(String),
+    #[error("On spawn: container has no row named `{0}`.")]
+    UnknownRow(String),
+    #[error("On spawn: bad value for row `{row}`: `{message}`.")]
+    BadValue { row: String, message: String },
+    #[error("On spawn: {0}")]
+    Cont(#[from] ContError),
+}
+
+/// Containers whose rows can be written by name from a [`Prefab`].
+/// Implemented by `#[derive(ContPrefab)]`.
+pub trait ContPrefab: ContSpawn {
+    fn write_prefab_row(
+        &mut self,
+        entity: Entity<Self>,
+        row: &str,
+        value: &PrefabValue,
+    ) -> Result<(), PrefabError>;
+}
+
+#[doc(hidden)]
+pub fn parse_prefab_value<T: DeserializeOwned>(
+    row: &str,
+    value: &PrefabValue,
+) -> Result<T, PrefabError> {
+    value
+        .clone()
+        .into_rust()
+        .map_err(|e| PrefabError::BadValue {
+            row: row.to_owned(),
+            message: format!("{}", e),
+        })
+}
+
+/// Initial row values for one entity and its children, usually loaded from a RON file.
+/// Rows that are not listed keep the initial value of the [`Row`].
+///
+/// ```ron
+/// Prefab(
+///     rows: {
+///         "health": 30.0,
+///         "boss": true,
+///     },
+///     children: [
+///         Prefab(rows: { "health": 5.0 }),
+///     ],
+/// )
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Prefab {
+    #[serde(default)]
+    pub rows: BTreeMap<String, PrefabValue>,
+    #[serde(default)]
+    pub children: Vec<Prefab>,
+}
+
+impl Prefab {
+    pub fn from_ron(data: &[u8]) -> Result<Self, PrefabError> {
+        ron::de::from_bytes(data).map_err(|e| PrefabError::Parse(format!("{}", e)))
+    }
+
+    /// Spawn this prefab into `cont`, parenting its children in `hierarchy`.
+    /// Returns the root entity.
+    ///
+    /// If the container fills up or any row fails to be written, every entity spawned so far is
+    /// despawned again.
+    pub fn spawn<C: ContPrefab>(
+        &self,
+        cont: &mut C,
+        hierarchy: &mut Hierarchy<C>,
+    ) -> Result<Entity<C>, PrefabError> {
+        self.spawn_with(cont, hierarchy, |_, _| Ok(()))
+    }
+
+    /// Like [`Prefab::spawn`], then run `overrides` on the root entity.
+    /// ```ignore
+    /// let boss = goblin.spawn_with(&mut enemies, &mut hierarchy, |enemies, goblin| {
+    ///     enemies.write_prefab_row(goblin, "boss", &PrefabValue::Bool(true))
+    /// })?;
+    /// ```
+    pub fn spawn_with<C: ContPrefab>(
+        &self,
+        cont: &mut C,
+        hierarchy: &mut Hierarchy<C>,
+        overrides: impl FnOnce(&mut C, Entity<C>) -> Result<(), PrefabError>,
+    ) -> Result<Entity<C>, PrefabError> {
+        let mut spawned = vec![];
+        let result = self
+            .spawn_tree(cont, hierarchy, None, &mut spawned)
+            .and_then(|root| overrides(cont, root).map(|_| root));
+
+        if result.is_err() {
+            for entity in spawned {
+                hierarchy.remove_parent(entity);
+                cont.despawn_reset(entity);
+            }
+        }
+        result
+    }
+
+    fn spawn_tree<C: ContPrefab>(
+        &self,
+        cont: &mut C,
+        hierarchy: &mut Hierarchy<C>,
+        parent: Option<Entity<C>>,
+        spawned: &mut Vec<Entity<C>>,
+    ) -> Result<Entity<C>, PrefabError> {
+        let entity = cont.try_spawn_empty()?;
+        spawned.push(entity);
+        if let Some(parent) = parent {
+            //  A freshly spawned entity has no descendants, so this cannot form a cycle.
+            hierarchy.set_parent(entity, parent).unwrap();
+        }
+
+        for (row, value) in &self.rows {
+            cont.write_prefab_row(entity, row, value)?;
+        }
+        for child in &self.children {
+            child.spawn_tree(cont, hierarchy, Some(entity), spawned)?;
+        }
+
+        Ok(entity)
+    }
+}
+
+/// A [`Prefab`] loaded through [`AssetLoaderEdgeData`] that follows changes to its file.
+pub struct PrefabAsset {
+    data: LoadedData,
+    prefab: Prefab,
+}
+
+impl PrefabAsset {
+    pub async fn load(loader: &AssetLoaderEdgeData, path: &str) -> anyhow::Result<Self> {
+        let mut data = loader.load(path).await?;
+        let prefab = Prefab::from_ron(&data.get_latest().await)?;
+        Ok(Self { data, prefab })
+    }
+
+    pub fn get(&self) -> &Prefab {
+        &self.prefab
+    }
+
+    /// Re-parse the prefab if its file changed since the last call.
+    /// Returns whether it was reloaded. On a parse error, the previous prefab is kept.
+    pub fn reload(&mut self) -> Result<bool, PrefabError> {
+        let Some(data) = self.data.try_get_latest() else {
+            return Ok(false);
+        };
+        self.prefab = Prefab::from_ron(&data)?;
+        Ok(true)
+    }
+}
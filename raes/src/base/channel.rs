@@ -0,0 +1,236 @@
+use crate::base::Edge;
+use parking_lot::RwLock;
+use std::{
+    collections::VecDeque,
+    marker::PhantomData,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+const DEFAULT_LIFETIME: u64 = 2;
+
+/// Ids of [`EventChannel`]s, so that readers can only be used with the channel that made them.
+static NEXT_CHANNEL: AtomicU64 = AtomicU64::new(0);
+
+struct Sent<T> {
+    frame: u64,
+    event: T,
+}
+
+struct ChannelState<T> {
+    events: VecDeque<Sent<T>>,
+    /// Sequence number of the front event.
+    oldest: u64,
+    frame: u64,
+}
+
+impl<T> ChannelState<T> {
+    fn head(&self) -> u64 {
+        self.oldest + self.events.len() as u64
+    }
+}
+
+/// Typed events sent between systems, which every [`EventReader`] reads at its own pace.
+///
+/// Unlike an [`EventBuffer`](crate::base::EventBuffer), events are not cleared for everyone at
+/// once. They are kept for `lifetime` frames, counted by [`EventChannel::end_frame`], so readers
+/// that run before or after the senders within a frame all see them. Readers that fall further
+/// behind miss the expired events and are told how many.
+///
+/// Share it through an `Arc` to send and read across threads, or publish it as an edge.
+pub struct EventChannel<T> {
+    state: RwLock<ChannelState<T>>,
+    lifetime: u64,
+    id: u64,
+}
+
+impl<T> EventChannel<T> {
+    /// `lifetime` is clamped to at least 1 frame.
+    pub fn new(lifetime: u64) -> Self {
+        Self {
+            state: RwLock::new(ChannelState {
+                events: VecDeque::new(),
+                oldest: 0,
+                frame: 0,
+            }),
+            lifetime: lifetime.max(1),
+            id: NEXT_CHANNEL.fetch_add(1, Ordering::Relaxed),
+        }
+    }
+
+    pub fn send(&self, event: T) {
+        let mut state = self.state.write();
+        let frame = state.frame;
+        state.events.push_back(Sent { frame, event });
+    }
+
+    pub fn send_batch(&self, events: impl IntoIterator<Item = T>) {
+        let mut state = self.state.write();
+        let frame = state.frame;
+        state
+            .events
+            .extend(events.into_iter().map(|event| Sent { frame, event }));
+    }
+
+    /// A reader of the events sent from now on.
+    pub fn reader(&self) -> EventReader<T> {
+        EventReader {
+            channel: self.id,
+            cursor: self.state.read().head(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Clone out the events `reader` has not seen yet, and move it past them.
+    /// The channel is not locked while they are iterated, so handlers may send more events.
+    ///
+    /// Panics if `reader` was made by another channel.
+    pub fn read(&self, reader: &mut EventReader<T>) -> EventRead<T>
+    where
+        T: Clone,
+    {
+        self.check_reader(reader, "read");
+        let state = self.state.read();
+        let missed = state.oldest.saturating_sub(reader.cursor);
+        let start = reader
+            .cursor
+            .saturating_sub(state.oldest)
+            .min(state.events.len() as u64);
+        reader.cursor = state.head();
+        let events = state
+            .events
+            .range(start as usize..)
+            .map(|sent| sent.event.clone())
+            .collect();
+        drop(state);
+
+        EventRead { events, missed }
+    }
+
+    fn check_reader(&self, reader: &EventReader<T>, action: &str) {
+        assert!(
+            reader.channel == self.id,
+            "On {}: EventReader<{}> was made by another channel.",
+            action,
+            std::any::type_name::<T>()
+        );
+    }
+
+    /// Drop the events that have outlived the lifetime.
+    pub fn end_frame(&self) {
+        let mut state = self.state.write();
+        state.frame += 1;
+        while let Some(sent) = state.events.front() {
+            if state.frame - sent.frame < self.lifetime {
+                break;
+            }
+            state.events.pop_front();
+            state.oldest += 1;
+        }
+    }
+
+    pub fn lifetime(&self) -> u64 {
+        self.lifetime
+    }
+
+    /// The number of [`EventChannel::end_frame`] calls so far.
+    pub fn frame(&self) -> u64 {
+        self.state.read().frame
+    }
+
+    /// Events kept by the channel, read or not.
+    pub fn len(&self) -> usize {
+        self.state.read().events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.state.read().events.is_empty()
+    }
+}
+
+impl<T> Default for EventChannel<T> {
+    fn default() -> Self {
+        Self::new(DEFAULT_LIFETIME)
+    }
+}
+
+/// Expired events are dropped at frame boundaries.
+impl<T: Send + Sync + 'static> Edge for EventChannel<T> {
+    fn end_frame(&self) {
+        EventChannel::end_frame(self)
+    }
+}
+
+/// The position of one reader in the [`EventChannel`] that made it.
+pub struct EventReader<T> {
+    channel: u64,
+    cursor: u64,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> EventReader<T> {
+    /// Whether `channel` has events this reader has not read yet.
+    ///
+    /// Panics if this reader was made by another channel.
+    pub fn has_unread(&self, channel: &EventChannel<T>) -> bool {
+        channel.check_reader(self, "has_unread");
+        self.cursor < channel.state.read().head()
+    }
+}
+
+impl<T> Clone for EventReader<T> {
+    fn clone(&self) -> Self {
+        Self {
+            channel: self.channel,
+            cursor: self.cursor,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for EventReader<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EventReader")
+            .field("channel", &self.channel)
+            .field("cursor", &self.cursor)
+            .finish()
+    }
+}
+
+/// The unread events of one [`EventChannel::read`], in the order they were sent.
+#[derive(Debug, Clone)]
+pub struct EventRead<T> {
+    events: Vec<T>,
+    missed: u64,
+}
+
+impl<T> EventRead<T> {
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &T> {
+        self.events.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Events that expired before the reader got to them.
+    pub fn missed(&self) -> u64 {
+        self.missed
+    }
+
+    pub fn lagged(&self) -> bool {
+        self.missed > 0
+    }
+}
+
+impl<T> IntoIterator for EventRead<T> {
+    type Item = T;
+    type IntoIter = std::vec::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.events.into_iter()
+    }
+}
@@ -0,0 +1,242 @@
+use super::*;
+use std::{
+    fmt,
+    marker::PhantomData,
+    ops::Deref,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+/// Ids of [`Commands`] batches, unique across every buffer.
+static NEXT_BATCH: AtomicU64 = AtomicU64::new(0);
+
+fn next_batch() -> u64 {
+    NEXT_BATCH.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Containers that can spawn an entity from the initial values of its rows.
+/// Implemented by `#[derive(Cont)]`, where `Init` is a tuple of every `Row`'s type.
+pub trait ContSpawn: Cont + Sized {
+    type Init;
+
+    /// Returns [`ContError::CapacityExceeded`] once a fixed `MAX_ENTITIES` is exceeded.
+    fn try_spawn_init(&mut self, init: Self::Init) -> Result<Entity<Self>, ContError>;
+    /// Spawn an entity whose rows keep their initial values.
+    fn try_spawn_empty(&mut self) -> Result<Entity<Self>, ContError>;
+
+    /// Panics once a fixed `MAX_ENTITIES` is exceeded, see [`ContSpawn::try_spawn_init`].
+    fn spawn_init(&mut self, init: Self::Init) -> Entity<Self> {
+        self.try_spawn_init(init)
+            .unwrap_or_else(|e| panic!("On spawn: {}", e))
+    }
+
+    /// Panics once a fixed `MAX_ENTITIES` is exceeded, see [`ContSpawn::try_spawn_empty`].
+    fn spawn_empty(&mut self) -> Entity<Self> {
+        self.try_spawn_empty()
+            .unwrap_or_else(|e| panic!("On spawn: {}", e))
+    }
+
+    fn despawn_reset(&mut self, entity: Entity<Self>) -> Option<()>;
+    fn has_entity(&self, entity: &Entity<Self>) -> bool;
+}
+
+/// A handle to an entity recorded in [`Commands`] that has not been spawned yet.
+///
+/// Only valid for the batch it was recorded in, up to and including its [`Commands::apply`].
+pub struct Provisional<C: Cont> {
+    batch: u64,
+    index: usize,
+    _phantom: PhantomData<fn() -> C>,
+}
+
+impl<C: Cont> Provisional<C> {
+    /// Position of this spawn in the [`Spawned`] list returned by [`Commands::apply`].
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns `None` if `spawned` was returned for a different batch, or if this spawn failed.
+    pub fn resolve(&self, spawned: &Spawned<C>) -> Option<Entity<C>> {
+        if self.batch != spawned.batch {
+            None?
+        }
+        spawned.entities.get(self.index)?.ok()
+    }
+}
+
+impl<C: Cont> Clone for Provisional<C> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<C: Cont> Copy for Provisional<C> {}
+
+/// Either a live entity or one that will be spawned when [`Commands`] are applied.
+pub enum Target<C: Cont> {
+    Entity(Entity<C>),
+    Provisional(Provisional<C>),
+}
+
+impl<C: Cont> Clone for Target<C> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<C: Cont> Copy for Target<C> {}
+
+impl<C: Cont> From<Entity<C>> for Target<C> {
+    fn from(entity: Entity<C>) -> Self {
+        Target::Entity(entity)
+    }
+}
+
+impl<C: Cont> From<Provisional<C>> for Target<C> {
+    fn from(provisional: Provisional<C>) -> Self {
+        Target::Provisional(provisional)
+    }
+}
+
+type Write<C> = Box<dyn FnOnce(&mut C, Entity<C>) + Send>;
+
+enum Command<C: ContSpawn> {
+    Spawn(C::Init),
+    Despawn(Target<C>),
+    Write(Target<C>, Write<C>),
+}
+
+/// Spawns, despawns and row writes recorded while a container is borrowed,
+/// then applied in one batch with [`Commands::apply`].
+///
+/// Commands are applied in the order they were recorded.
+/// Despawns and writes that target an entity that is no longer alive are skipped, as are those
+/// that target a [`Provisional`] of another buffer or of an already applied batch.
+/// Spawns that fail, such as once a fixed container is full, are skipped along with the commands
+/// targeting them, and reported in the returned [`Spawned`].
+pub struct Commands<C: ContSpawn> {
+    commands: Vec<Command<C>>,
+    spawn_count: usize,
+    batch: u64,
+}
+
+impl<C: ContSpawn> Commands<C> {
+    pub fn new() -> Self {
+        Self {
+            commands: vec![],
+            spawn_count: 0,
+            batch: next_batch(),
+        }
+    }
+
+    pub fn spawn(&mut self, init: C::Init) -> Provisional<C> {
+        let provisional = Provisional {
+            batch: self.batch,
+            index: self.spawn_count,
+            _phantom: PhantomData,
+        };
+        self.spawn_count += 1;
+        self.commands.push(Command::Spawn(init));
+        provisional
+    }
+
+    pub fn despawn(&mut self, target: impl Into<Target<C>>) {
+        self.commands.push(Command::Despawn(target.into()));
+    }
+
+    /// Record a write to the rows of `target`.
+    /// ```ignore
+    /// commands.write(bullet, |bullets: &mut Bullets, bullet| *bullets.speed_mut(bullet) = 2.0);
+    /// ```
+    pub fn write(
+        &mut self,
+        target: impl Into<Target<C>>,
+        write: impl FnOnce(&mut C, Entity<C>) + Send + 'static,
+    ) {
+        self.commands
+            .push(Command::Write(target.into(), Box::new(write)));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// Apply and clear every recorded command, starting a new batch.
+    /// Returns the result of every spawn, indexed by [`Provisional::index`].
+    pub fn apply(&mut self, cont: &mut C) -> Spawned<C> {
+        let mut spawned = Spawned {
+            batch: self.batch,
+            entities: Vec::with_capacity(self.spawn_count),
+        };
+        let resolve = |spawned: &Spawned<C>, target: Target<C>| match target {
+            Target::Entity(entity) => Some(entity),
+            Target::Provisional(provisional) => provisional.resolve(spawned),
+        };
+
+        for command in self.commands.drain(..) {
+            match command {
+                Command::Spawn(init) => spawned.entities.push(cont.try_spawn_init(init)),
+                Command::Despawn(target) => {
+                    if let Some(entity) = resolve(&spawned, target) {
+                        cont.despawn_reset(entity);
+                    }
+                }
+                Command::Write(target, write) => {
+                    if let Some(entity) = resolve(&spawned, target) {
+                        if cont.has_entity(&entity) {
+                            write(cont, entity);
+                        }
+                    }
+                }
+            }
+        }
+        self.spawn_count = 0;
+        self.batch = next_batch();
+
+        spawned
+    }
+}
+
+/// The results of the spawns of one [`Commands::apply`], in the order they were recorded.
+pub struct Spawned<C: Cont> {
+    batch: u64,
+    entities: Vec<Result<Entity<C>, ContError>>,
+}
+
+impl<C: Cont> Spawned<C> {
+    /// The entities that were spawned, skipping failed spawns.
+    pub fn entities(&self) -> impl Iterator<Item = Entity<C>> + '_ {
+        self.entities.iter().filter_map(|result| result.ok())
+    }
+
+    /// The [`Provisional::index`] and error of every failed spawn.
+    pub fn failed(&self) -> impl Iterator<Item = (usize, ContError)> + '_ {
+        self.entities
+            .iter()
+            .enumerate()
+            .filter_map(|(index, result)| Some((index, result.err()?)))
+    }
+
+    pub fn into_vec(self) -> Vec<Result<Entity<C>, ContError>> {
+        self.entities
+    }
+}
+
+impl<C: Cont> Deref for Spawned<C> {
+    type Target = [Result<Entity<C>, ContError>];
+
+    fn deref(&self) -> &Self::Target {
+        &self.entities
+    }
+}
+
+impl<C: Cont> fmt::Debug for Spawned<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(&self.entities).finish()
+    }
+}
+
+impl<C: ContSpawn> Default for Commands<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,74 +1,270 @@
 use super::*;
-use std::marker::PhantomData;
+use crate::base::{EventBuffer, EventBufferConfig, Flushable};
+use lifecycle::Lifecycle;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    sync::Arc,
+};
 
-#[derive(Clone, Copy)]
+/// A handle to an entity of container `C`.
+///
+/// Handles are only meaningful to the [`ContEntities`] that spawned them.
+/// For references that survive slot reshuffling, see [`StableIds`].
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
 pub struct Entity<C: Cont> {
     id: usize,
     generation: usize,
-    _phantom: PhantomData<C>,
+    //  `fn() -> C` keeps `Entity` `Send`, `Sync` and friends no matter what `C` is.
+    #[serde(skip)]
+    _phantom: PhantomData<fn() -> C>,
 }
 
 impl<C: Cont> Entity<C> {
     pub fn id(&self) -> usize {
         self.id
     }
+
+    pub(super) fn new(id: usize, generation: usize) -> Self {
+        Self {
+            id,
+            generation,
+            _phantom: PhantomData,
+        }
+    }
+
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+}
+
+//  Implemented by hand, since derives would require `C` to implement each trait as well.
+
+impl<C: Cont> Clone for Entity<C> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<C: Cont> Copy for Entity<C> {}
+
+impl<C: Cont> fmt::Debug for Entity<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Entity<{}>({}v{})",
+            std::any::type_name::<C>(),
+            self.id,
+            self.generation
+        )
+    }
+}
+
+impl<C: Cont> PartialEq for Entity<C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id && self.generation == other.generation
+    }
+}
+
+impl<C: Cont> Eq for Entity<C> {}
+
+impl<C: Cont> Hash for Entity<C> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+        self.generation.hash(state);
+    }
 }
 
-#[derive(Debug, Clone, Copy)]
+impl<C: Cont> PartialOrd for Entity<C> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<C: Cont> Ord for Entity<C> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.id, self.generation).cmp(&(other.id, other.generation))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 struct Generation(usize);
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 struct Exists(bool);
 
-#[derive(Debug, Clone, Copy)]
+/// The entity table of a container.
+///
+/// Slots are heap allocated.
+/// With a fixed `MAX_ENTITIES`, spawning past capacity panics.
+/// With [`GROWABLE`], the table grows instead.
+///
+/// Free slots are kept on a free list, so spawning and despawning are constant time.
+/// A slot whose generation would overflow is retired rather than reused,
+/// so old handles to it can never alias a new entity.
+///
+/// Serializing keeps generations and the free list,
+/// so a reloaded table still rejects handles that were stale when it was saved.
+/// Deserializing checks that the table is consistent, see [`ContEntities::validate`].
+///
+/// Observers registered with [`ContEntities::on_spawn`] and [`ContEntities::on_despawn`] are
+/// called as entities come and go. With [`ContEntities::buffer_lifecycle`], entities spawned and
+/// despawned since the last [`Flushable::flush`] are also buffered for systems that process them
+/// in bulk.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "", try_from = "RawEntities")]
 pub struct ContEntities<C: Cont, const MAX_ENTITIES: usize = DEFAULT_MAX_ENTITIES> {
-    entities: [(Generation, Exists); MAX_ENTITIES],
-    _phantom: PhantomData<C>,
+    entities: Vec<(Generation, Exists)>,
+    free: Vec<usize>,
+    #[serde(skip)]
+    lifecycle: Lifecycle<C>,
+    #[serde(skip)]
+    _phantom: PhantomData<fn() -> C>,
+}
+
+/// The serialized form of a [`ContEntities`], before it is validated.
+#[derive(Deserialize)]
+struct RawEntities {
+    entities: Vec<(Generation, Exists)>,
+    free: Vec<usize>,
+}
+
+impl<C: Cont, const MAX_ENTITIES: usize> TryFrom<RawEntities> for ContEntities<C, MAX_ENTITIES> {
+    type Error = String;
+
+    fn try_from(raw: RawEntities) -> Result<Self, Self::Error> {
+        let entities = Self {
+            entities: raw.entities,
+            free: raw.free,
+            lifecycle: Lifecycle::default(),
+            _phantom: PhantomData,
+        };
+        entities.validate()?;
+        Ok(entities)
+    }
 }
 
 impl<C: Cont, const MAX_ENTITIES: usize> ContEntities<C, MAX_ENTITIES> {
     pub fn new() -> Self {
+        let (entities, free) = if MAX_ENTITIES == GROWABLE {
+            (vec![], vec![])
+        } else {
+            //  Reversed so that the lowest ids are handed out first.
+            (
+                vec![(Generation(0), Exists(false)); MAX_ENTITIES],
+                (0..MAX_ENTITIES).rev().collect(),
+            )
+        };
         Self {
-            entities: [(Generation(0), Exists(false)); MAX_ENTITIES],
+            entities,
+            free,
+            lifecycle: Lifecycle::default(),
             _phantom: PhantomData,
         }
     }
 
+    /// Panics once a fixed `MAX_ENTITIES` is exceeded, see [`ContEntities::try_spawn`].
     pub fn spawn(&mut self) -> Entity<C> {
-        self.entities
-            .iter_mut()
-            .enumerate()
-            .find_map(|(id, (Generation(generation), Exists(exists)))| {
-                (!*exists).then(|| {
-                    *exists = true;
-                    Entity {
-                        id,
-                        generation: *generation,
-                        _phantom: PhantomData,
-                    }
-                })
-            })
-            .unwrap_or_else(|| {
-                panic!(
-                    "On spawn: exceeded max entities of {} for container {}.",
-                    MAX_ENTITIES,
-                    std::any::type_name::<C>()
-                );
-            })
+        self.try_spawn()
+            .unwrap_or_else(|e| panic!("On spawn: {}", e))
     }
 
+    pub fn try_spawn(&mut self) -> Result<Entity<C>, ContError> {
+        let id = if let Some(id) = self.free.pop() {
+            id
+        } else if MAX_ENTITIES == GROWABLE {
+            self.entities.push((Generation(0), Exists(false)));
+            self.entities.len() - 1
+        } else {
+            Err(ContError::CapacityExceeded {
+                cont: std::any::type_name::<C>(),
+                max: MAX_ENTITIES,
+            })?
+        };
+
+        let (Generation(generation), Exists(exists)) = &mut self.entities[id];
+        *exists = true;
+        let entity = Entity::new(id, *generation);
+        self.lifecycle.spawned(entity);
+        Ok(entity)
+    }
+
+    /// Returns `None` if `entity` is not alive, see [`ContEntities::try_despawn`].
     pub fn despawn(&mut self, entity: Entity<C>) -> Option<()> {
-        if self.has_entity(&entity) {
-            None?
+        self.try_despawn(entity).ok()
+    }
+
+    pub fn try_despawn(&mut self, entity: Entity<C>) -> Result<(), ContError> {
+        if let Err(e) = self.check_entity(&entity) {
+            //  The slot only moves past a handle's generation once that handle is despawned,
+            //  except for a retired slot, which stays dead at the last generation. A dead slot at
+            //  the handle's generation was otherwise never spawned with it.
+            let despawned = self
+                .entities
+                .get(entity.id())
+                .map(|&(Generation(generation), _)| {
+                    generation > entity.generation
+                        || (generation == entity.generation && generation == usize::MAX)
+                })
+                .unwrap_or(false);
+            Err(if despawned {
+                ContError::DoubleDespawn {
+                    cont: std::any::type_name::<C>(),
+                    id: entity.id(),
+                    generation: entity.generation,
+                }
+            } else {
+                e
+            })?
         }
 
-        let (Generation(generation), Exists(exists)) = self.entities.get_mut(entity.id()).unwrap();
-        *generation += 1;
+        let (Generation(generation), Exists(exists)) = &mut self.entities[entity.id()];
         *exists = false;
 
-        Some(())
+        //  An overflowing slot stays out of the free list for good.
+        if let Some(next_generation) = generation.checked_add(1) {
+            *generation = next_generation;
+            self.free.push(entity.id());
+        }
+
+        self.lifecycle.despawned(entity);
+        Ok(())
+    }
+
+    /// Call `observer` with every entity right after it is spawned.
+    pub fn on_spawn(&mut self, observer: impl Fn(Entity<C>) + Send + Sync + 'static) {
+        self.lifecycle.on_spawn(Arc::new(observer));
     }
 
-    pub fn has_entity(&mut self, entity: &Entity<C>) -> bool {
+    /// Call `observer` with every entity right after it is despawned.
+    pub fn on_despawn(&mut self, observer: impl Fn(Entity<C>) + Send + Sync + 'static) {
+        self.lifecycle.on_despawn(Arc::new(observer));
+    }
+
+    /// Buffer the entities spawned and despawned until the next flush, starting now.
+    /// Containers that are never flushed should pick an overflow policy other than
+    /// [`Overflow::Spill`](crate::base::Overflow::Spill), or the buffers grow without bound.
+    pub fn buffer_lifecycle(&mut self, config: EventBufferConfig) {
+        self.lifecycle.buffer(config);
+    }
+
+    /// Entities spawned since the last flush, in spawn order.
+    /// `None` unless [`ContEntities::buffer_lifecycle`] was called.
+    pub fn spawned(&self) -> Option<&EventBuffer<Entity<C>>> {
+        self.lifecycle.spawned_buffer()
+    }
+
+    /// Entities despawned since the last flush, in despawn order.
+    /// `None` unless [`ContEntities::buffer_lifecycle`] was called.
+    pub fn despawned(&self) -> Option<&EventBuffer<Entity<C>>> {
+        self.lifecycle.despawned_buffer()
+    }
+
+    pub fn has_entity(&self, entity: &Entity<C>) -> bool {
         self.entities
             .get(entity.id())
             .map(|&(Generation(generation), Exists(exists))| {
@@ -76,9 +272,279 @@ impl<C: Cont, const MAX_ENTITIES: usize> ContEntities<C, MAX_ENTITIES> {
             })
             .unwrap_or(false)
     }
+
+    /// Like [`ContEntities::has_entity`], but returns [`ContError::StaleHandle`] for entities that
+    /// are not alive.
+    pub fn check_entity(&self, entity: &Entity<C>) -> Result<(), ContError> {
+        if !self.has_entity(entity) {
+            Err(ContError::StaleHandle {
+                cont: std::any::type_name::<C>(),
+                id: entity.id(),
+                generation: entity.generation,
+            })?
+        }
+        Ok(())
+    }
+
+    /// Iterate over every live entity in id order.
+    pub fn iter(&self) -> Iter<'_, C> {
+        Iter {
+            entities: &self.entities,
+            next_id: 0,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Iterate over every live entity along with its data from `query`.
+    ///
+    /// `query` is a tuple of rows, borrowed either shared or mutably.
+    /// ```ignore
+    /// for (entity, position, mut velocity) in self.entities.query((&self.positions, &mut self.velocities)) {
+    ///     *velocity += *position;
+    /// }
+    /// ```
+    pub fn query<Q: Query<C>>(&self, query: Q) -> QueryIter<'_, C, Q> {
+        self.query_filtered(query, ())
+    }
+
+    /// Like [`ContEntities::query`], but only visits entities that pass `filter`.
+    ///
+    /// `filter` is a tuple of [`With`] and [`Without`] tags.
+    /// Whole words of 64 entities that fail the filter are skipped at once.
+    /// ```ignore
+    /// let filter = (With(&self.visible), Without(&self.frozen));
+    /// for (entity, position) in self.entities.query_filtered((&mut self.positions,), filter) {}
+    /// ```
+    pub fn query_filtered<Q: Query<C>, F: Filter>(
+        &self,
+        query: Q,
+        filter: F,
+    ) -> QueryIter<'_, C, Q, F> {
+        let cursor = query.cursor(self.capacity());
+        QueryIter::new(self.iter(), cursor, filter)
+    }
+
+    /// Like [`ContEntities::query`], but splits the entity table into chunks that are processed on
+    /// rayon's thread pool.
+    ///
+    /// The query's rows stay borrowed for the whole call and `f` can only capture shared state,
+    /// so no row can be mutably aliased between workers.
+    pub fn par_query<Q, F>(&self, query: Q, f: F)
+    where
+        Q: Query<C>,
+        Q::Cursor: Sync,
+        F: Fn(Q::Item) + Send + Sync,
+    {
+        let cursor = query.cursor(self.capacity());
+        let cursor = &cursor;
+        self.entities
+            .par_chunks(PAR_CHUNK_SIZE)
+            .enumerate()
+            .for_each(|(chunk_index, chunk)| {
+                let chunk_start = chunk_index * PAR_CHUNK_SIZE;
+                for (offset, &(Generation(generation), Exists(exists))) in chunk.iter().enumerate()
+                {
+                    if !exists {
+                        continue;
+                    }
+                    let entity = Entity::new(chunk_start + offset, generation);
+                    //  Chunks are disjoint, so every live entity is fetched exactly once.
+                    if let Some(item) = unsafe { cursor.fetch(entity) } {
+                        f(item);
+                    }
+                }
+            });
+    }
+
+    /// Check that a fixed table has exactly `MAX_ENTITIES` slots, and that the free list holds
+    /// every dead slot that can still be reused exactly once.
+    fn validate(&self) -> Result<(), String> {
+        let cont = std::any::type_name::<C>();
+        if MAX_ENTITIES != GROWABLE && self.entities.len() != MAX_ENTITIES {
+            Err(format!(
+                "entity table of container {} has {} slots instead of {}.",
+                cont,
+                self.entities.len(),
+                MAX_ENTITIES
+            ))?
+        }
+
+        let mut in_free = vec![false; self.entities.len()];
+        for &id in &self.free {
+            match self.entities.get(id) {
+                None => Err(format!(
+                    "free id {} of container {} is out of range of {} slots.",
+                    id,
+                    cont,
+                    self.entities.len()
+                ))?,
+                Some((_, Exists(true))) => {
+                    Err(format!("free id {} of container {} is alive.", id, cont))?
+                }
+                Some(_) if in_free[id] => Err(format!(
+                    "free id {} of container {} is listed twice.",
+                    id, cont
+                ))?,
+                Some(_) => in_free[id] = true,
+            }
+        }
+
+        //  Retired slots are the only dead slots left out of the free list.
+        let lost = self.entities.iter().zip(&in_free).position(
+            |(&(Generation(generation), Exists(exists)), &free)| {
+                !exists && !free && generation != usize::MAX
+            },
+        );
+        if let Some(id) = lost {
+            Err(format!(
+                "dead id {} of container {} is missing from the free list.",
+                id, cont
+            ))?
+        }
+
+        Ok(())
+    }
+
+    /// The number of slots currently allocated.
+    /// Every live entity has an id below this.
+    pub fn capacity(&self) -> usize {
+        self.entities.len()
+    }
+}
+
+pub struct Iter<'a, C: Cont> {
+    entities: &'a [(Generation, Exists)],
+    next_id: usize,
+    _phantom: PhantomData<fn() -> C>,
+}
+
+impl<'a, C: Cont> Iter<'a, C> {
+    /// The id of the next slot to be visited.
+    pub(super) fn position(&self) -> usize {
+        self.next_id
+    }
+
+    /// Returns whether any slots are left to visit.
+    pub(super) fn skip_to(&mut self, id: usize) -> bool {
+        self.next_id = self.next_id.max(id);
+        self.next_id < self.entities.len()
+    }
+
+    /// Visit the next slot, live or not.
+    /// Returns `None` once every slot was visited.
+    pub(super) fn step(&mut self) -> Option<Option<Entity<C>>> {
+        let &(Generation(generation), Exists(exists)) = self.entities.get(self.next_id)?;
+        let id = self.next_id;
+        self.next_id += 1;
+        Some(exists.then(|| Entity::new(id, generation)))
+    }
+}
+
+impl<'a, C: Cont> Iterator for Iter<'a, C> {
+    type Item = Entity<C>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(entity) = self.step()? {
+                return Some(entity);
+            }
+        }
+    }
+}
+
+/// The slots and free list of a [`ContEntities`], without its observers or lifecycle buffers.
+pub struct EntitiesSnapshot<C: Cont> {
+    entities: Vec<(Generation, Exists)>,
+    free: Vec<usize>,
+    _phantom: PhantomData<fn() -> C>,
+}
+
+impl<C: Cont> Clone for EntitiesSnapshot<C> {
+    fn clone(&self) -> Self {
+        Self {
+            entities: self.entities.clone(),
+            free: self.free.clone(),
+            _phantom: PhantomData,
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.entities.clone_from(&source.entities);
+        self.free.clone_from(&source.free);
+    }
+}
+
+pub struct EntitiesDelta<C: Cont> {
+    pub spawned: Vec<Entity<C>>,
+    pub despawned: Vec<Entity<C>>,
+}
+
+impl<C: Cont> fmt::Debug for EntitiesDelta<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EntitiesDelta")
+            .field("spawned", &self.spawned)
+            .field("despawned", &self.despawned)
+            .finish()
+    }
+}
+
+impl<C: Cont> Delta for EntitiesSnapshot<C> {
+    type Delta = EntitiesDelta<C>;
+
+    fn delta(&self, newer: &Self) -> Self::Delta {
+        let mut delta = EntitiesDelta {
+            spawned: vec![],
+            despawned: vec![],
+        };
+        let dead = (Generation(0), Exists(false));
+        let len = self.entities.len().max(newer.entities.len());
+        for id in 0..len {
+            let (Generation(old_generation), Exists(old_exists)) =
+                *self.entities.get(id).unwrap_or(&dead);
+            let (Generation(new_generation), Exists(new_exists)) =
+                *newer.entities.get(id).unwrap_or(&dead);
+            let same = old_exists && new_exists && old_generation == new_generation;
+            if old_exists && !same {
+                delta.despawned.push(Entity::new(id, old_generation));
+            }
+            if new_exists && !same {
+                delta.spawned.push(Entity::new(id, new_generation));
+            }
+        }
+        delta
+    }
+}
+
+impl<C: Cont, const MAX_ENTITIES: usize> Snapshot for ContEntities<C, MAX_ENTITIES> {
+    type State = EntitiesSnapshot<C>;
+
+    fn snapshot(&self) -> Self::State {
+        EntitiesSnapshot {
+            entities: self.entities.clone(),
+            free: self.free.clone(),
+            _phantom: PhantomData,
+        }
+    }
+
+    fn snapshot_into(&self, state: &mut Self::State) {
+        state.entities.clone_from(&self.entities);
+        state.free.clone_from(&self.free);
+    }
+
+    /// Observers are not called for entities that appear or disappear.
+    fn restore(&mut self, state: &Self::State) {
+        self.entities.clone_from(&state.entities);
+        self.free.clone_from(&state.free);
+    }
+}
+
+impl<C: Cont, const MAX_ENTITIES: usize> Flushable for ContEntities<C, MAX_ENTITIES> {
+    fn flush(&mut self) {
+        self.lifecycle.flush();
+    }
 }
 
-impl<C: Cont> Default for ContEntities<C> {
+impl<C: Cont, const MAX_ENTITIES: usize> Default for ContEntities<C, MAX_ENTITIES> {
     fn default() -> Self {
         Self::new()
     }
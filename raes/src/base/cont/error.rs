@@ -0,0 +1,28 @@
+use thiserror::Error;
+
+/// Misuse of a container, returned by the `try_` and `checked_` methods.
+/// The panicking methods panic with the same message.
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContError {
+    #[error("exceeded max entities of {max} for container {cont}.")]
+    CapacityExceeded { cont: &'static str, max: usize },
+    #[error("entity id {id} exceeded max entities of {max} for container {cont}, row type {row}.")]
+    OutOfRange {
+        cont: &'static str,
+        row: &'static str,
+        id: usize,
+        max: usize,
+    },
+    #[error("entity {id}v{generation} of container {cont} is not alive.")]
+    StaleHandle {
+        cont: &'static str,
+        id: usize,
+        generation: usize,
+    },
+    #[error("entity {id}v{generation} of container {cont} was already despawned.")]
+    DoubleDespawn {
+        cont: &'static str,
+        id: usize,
+        generation: usize,
+    },
+}
@@ -0,0 +1,183 @@
+use super::*;
+use cgmath::{
+    BaseFloat, Basis2, Decomposed, Matrix3, Matrix4, Quaternion, Transform, Vector2, Vector3,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HierarchyError {
+    #[error("On set_parent: an entity cannot be its own ancestor.")]
+    Cycle,
+}
+
+/// A transform that can be combined with the transform of its parent.
+pub trait Compose {
+    /// `self` is the parent's world transform and `local` is the child's local transform.
+    fn compose(&self, local: &Self) -> Self;
+}
+
+impl<S: BaseFloat> Compose for Matrix3<S> {
+    fn compose(&self, local: &Self) -> Self {
+        self * local
+    }
+}
+
+impl<S: BaseFloat> Compose for Matrix4<S> {
+    fn compose(&self, local: &Self) -> Self {
+        self * local
+    }
+}
+
+impl<S: BaseFloat> Compose for Decomposed<Vector2<S>, Basis2<S>> {
+    fn compose(&self, local: &Self) -> Self {
+        self.concat(local)
+    }
+}
+
+impl<S: BaseFloat> Compose for Decomposed<Vector3<S>, Quaternion<S>> {
+    fn compose(&self, local: &Self) -> Self {
+        self.concat(local)
+    }
+}
+
+/// Parent/child relationships between the entities of a container.
+///
+/// Entities without a live parent are roots.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct Hierarchy<C: Cont> {
+    parents: SparseRow<Entity<C>>,
+    children: SparseRow<Vec<Entity<C>>>,
+}
+
+impl<C: Cont> Hierarchy<C> {
+    pub fn new() -> Self {
+        Self {
+            parents: SparseRow::new(),
+            children: SparseRow::new(),
+        }
+    }
+
+    /// Attach `child` to `parent`, detaching it from its previous parent.
+    pub fn set_parent(
+        &mut self,
+        child: Entity<C>,
+        parent: Entity<C>,
+    ) -> Result<(), HierarchyError> {
+        let mut ancestor = Some(parent);
+        while let Some(current) = ancestor {
+            if current == child {
+                Err(HierarchyError::Cycle)?
+            }
+            ancestor = self.parent(current);
+        }
+
+        self.remove_parent(child);
+        self.parents.insert(child, parent);
+        if let Some(siblings) = self.children.get_mut(parent) {
+            siblings.push(child);
+        } else {
+            self.children.insert(parent, vec![child]);
+        }
+
+        Ok(())
+    }
+
+    /// Detach `child`, making it a root.
+    /// Returns the previous parent.
+    pub fn remove_parent(&mut self, child: Entity<C>) -> Option<Entity<C>> {
+        let parent = self.parents.remove(child)?;
+        if let Some(siblings) = self.children.get_mut(parent) {
+            siblings.retain(|&sibling| sibling != child);
+            if siblings.is_empty() {
+                self.children.remove(parent);
+            }
+        }
+        Some(parent)
+    }
+
+    pub fn parent(&self, child: Entity<C>) -> Option<Entity<C>> {
+        self.parents.get(child).copied()
+    }
+
+    pub fn children(&self, parent: Entity<C>) -> &[Entity<C>] {
+        self.children
+            .get(parent)
+            .map(|children| children.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// Despawn `entity` along with all of its descendants, resetting their rows.
+    pub fn despawn_recursive(&mut self, cont: &mut C, entity: Entity<C>) -> Option<()>
+    where
+        C: ContSpawn,
+    {
+        self.despawn_recursive_with(entity, |entity| cont.despawn_reset(entity))
+    }
+
+    /// Like [`Hierarchy::despawn_recursive`], but despawns every entity through `despawn`,
+    /// such as `|entity| entities.despawn(entity)` for a standalone [`ContEntities`].
+    /// Nothing is despawned if `despawn` fails for `entity` itself.
+    pub fn despawn_recursive_with(
+        &mut self,
+        entity: Entity<C>,
+        mut despawn: impl FnMut(Entity<C>) -> Option<()>,
+    ) -> Option<()> {
+        despawn(entity)?;
+
+        self.remove_parent(entity);
+        let mut stack = vec![entity];
+        while let Some(current) = stack.pop() {
+            if let Some(children) = self.children.remove(current) {
+                for &child in &children {
+                    self.parents.remove(child);
+                    despawn(child);
+                }
+                stack.extend(children);
+            }
+        }
+
+        Some(())
+    }
+
+    /// Compute the world transform of every live entity from its local transform and the world
+    /// transform of its parent.
+    /// Call once per frame after local transforms are updated.
+    pub fn propagate<T, const MAX_ENTITIES: usize, const ROW_MAX_ENTITIES: usize>(
+        &self,
+        entities: &ContEntities<C, MAX_ENTITIES>,
+        local: &Row<T, ROW_MAX_ENTITIES>,
+        world: &mut Row<T, ROW_MAX_ENTITIES>,
+    ) where
+        T: Compose + Clone,
+    {
+        let mut stack = vec![];
+        let roots = entities.iter().filter(|&entity| {
+            self.parent(entity)
+                .map(|parent| !entities.has_entity(&parent))
+                .unwrap_or(true)
+        });
+
+        for root in roots {
+            *world.get_mut(root) = local.get(root).clone();
+            stack.push(root);
+
+            while let Some(parent) = stack.pop() {
+                let parent_world = world.get(parent).clone();
+                for &child in self.children(parent) {
+                    if entities.has_entity(&child) {
+                        *world.get_mut(child) = parent_world.compose(local.get(child));
+                        stack.push(child);
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<C: Cont> Default for Hierarchy<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
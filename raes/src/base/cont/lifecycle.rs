@@ -0,0 +1,89 @@
+use super::*;
+use crate::base::{EventBuffer, EventBufferConfig};
+use std::{fmt, sync::Arc};
+
+pub(super) type Observer<C> = Arc<dyn Fn(Entity<C>) + Send + Sync>;
+
+/// Spawn and despawn observers of a [`ContEntities`], along with the entities spawned and
+/// despawned since the last flush once buffering is enabled.
+pub(super) struct Lifecycle<C: Cont> {
+    on_spawn: Vec<Observer<C>>,
+    on_despawn: Vec<Observer<C>>,
+    spawned: Option<EventBuffer<Entity<C>>>,
+    despawned: Option<EventBuffer<Entity<C>>>,
+}
+
+impl<C: Cont> Lifecycle<C> {
+    pub fn on_spawn(&mut self, observer: Observer<C>) {
+        self.on_spawn.push(observer);
+    }
+
+    pub fn on_despawn(&mut self, observer: Observer<C>) {
+        self.on_despawn.push(observer);
+    }
+
+    pub fn buffer(&mut self, config: EventBufferConfig) {
+        self.spawned = Some(EventBuffer::from_config(config));
+        self.despawned = Some(EventBuffer::from_config(config));
+    }
+
+    pub fn spawned(&mut self, entity: Entity<C>) {
+        if let Some(spawned) = &mut self.spawned {
+            spawned.push(entity);
+        }
+        self.on_spawn.iter().for_each(|observer| observer(entity));
+    }
+
+    pub fn despawned(&mut self, entity: Entity<C>) {
+        if let Some(despawned) = &mut self.despawned {
+            despawned.push(entity);
+        }
+        self.on_despawn.iter().for_each(|observer| observer(entity));
+    }
+
+    pub fn spawned_buffer(&self) -> Option<&EventBuffer<Entity<C>>> {
+        self.spawned.as_ref()
+    }
+
+    pub fn despawned_buffer(&self) -> Option<&EventBuffer<Entity<C>>> {
+        self.despawned.as_ref()
+    }
+
+    pub fn flush(&mut self) {
+        self.spawned.iter_mut().for_each(EventBuffer::flush);
+        self.despawned.iter_mut().for_each(EventBuffer::flush);
+    }
+}
+
+impl<C: Cont> Default for Lifecycle<C> {
+    fn default() -> Self {
+        Self {
+            on_spawn: vec![],
+            on_despawn: vec![],
+            spawned: None,
+            despawned: None,
+        }
+    }
+}
+
+impl<C: Cont> Clone for Lifecycle<C> {
+    fn clone(&self) -> Self {
+        Self {
+            on_spawn: self.on_spawn.clone(),
+            on_despawn: self.on_despawn.clone(),
+            spawned: self.spawned.clone(),
+            despawned: self.despawned.clone(),
+        }
+    }
+}
+
+impl<C: Cont> fmt::Debug for Lifecycle<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Lifecycle")
+            .field("on_spawn", &self.on_spawn.len())
+            .field("on_despawn", &self.on_despawn.len())
+            .field("spawned", &self.spawned)
+            .field("despawned", &self.despawned)
+            .finish()
+    }
+}
@@ -1,9 +1,39 @@
+mod commands;
 mod entity;
+mod error;
+mod hierarchy;
+mod lifecycle;
+mod query;
 mod row;
+mod snapshot;
+mod sparse;
+mod spatial;
+mod stable;
+mod tag;
+mod weak;
 
 const DEFAULT_MAX_ENTITIES: usize = 128;
+/// Number of entity slots handed to each worker in parallel queries.
+const PAR_CHUNK_SIZE: usize = 64;
+/// Number of entity slots per word of a [`TagRow`].
+const TAG_WORD_BITS: usize = u64::BITS as usize;
+
+/// Pass as `MAX_ENTITIES` to let a container grow on demand instead of panicking once full.
+pub const GROWABLE: usize = usize::MAX;
 
 pub trait Cont {}
 
-pub use entity::{ContEntities, Entity};
-pub use row::Row;
+pub use raes_derive::Cont;
+
+pub use commands::{Commands, ContSpawn, Provisional, Spawned, Target};
+pub use entity::{ContEntities, EntitiesDelta, EntitiesSnapshot, Entity, Iter};
+pub use error::ContError;
+pub use hierarchy::{Compose, Hierarchy, HierarchyError};
+pub use query::{Cursor, Fetch, Query, QueryCursor, QueryIter};
+pub use row::{Mut, Row, RowSnapshot};
+pub use snapshot::{Delta, Rollback, Snapshot};
+pub use sparse::SparseRow;
+pub use spatial::SpatialGrid;
+pub use stable::{StableId, StableIds};
+pub use tag::{Filter, TagRow, With, Without};
+pub use weak::WeakEntity;
@@ -0,0 +1,116 @@
+use super::*;
+
+/// A column that can be joined in a [`ContEntities::query`], such as `&Row<T>` or `&mut Row<T>`.
+pub trait Fetch {
+    type Item;
+    type Cursor: Cursor<Item = Self::Item>;
+
+    /// Called once before iteration with the number of slots in the entity table.
+    fn cursor(self, len: usize) -> Self::Cursor;
+}
+
+/// Random access into a column, shared between every worker of a parallel query.
+pub trait Cursor {
+    type Item;
+
+    /// Returns `None` to skip the entity.
+    ///
+    /// # Safety
+    ///
+    /// Each entity may be fetched at most once, since mutable columns hand out `&mut` items.
+    unsafe fn fetch<C: Cont>(&self, entity: &Entity<C>) -> Option<Self::Item>;
+}
+
+/// A tuple of [`Fetch`]es joined over the live entities of a container.
+pub trait Query<C: Cont> {
+    type Item;
+    type Cursor: QueryCursor<C, Item = Self::Item>;
+
+    fn cursor(self, len: usize) -> Self::Cursor;
+}
+
+pub trait QueryCursor<C: Cont> {
+    type Item;
+
+    /// # Safety
+    ///
+    /// Each entity may be fetched at most once.
+    unsafe fn fetch(&self, entity: Entity<C>) -> Option<Self::Item>;
+}
+
+macro_rules! impl_query {
+    ($($fetch:ident),+) => {
+        impl<C: Cont, $($fetch: Fetch),+> Query<C> for ($($fetch,)+) {
+            type Item = (Entity<C>, $($fetch::Item),+);
+            type Cursor = ($($fetch::Cursor,)+);
+
+            #[allow(non_snake_case)]
+            fn cursor(self, len: usize) -> Self::Cursor {
+                let ($($fetch,)+) = self;
+                ($($fetch.cursor(len),)+)
+            }
+        }
+
+        impl<C: Cont, $($fetch: Cursor),+> QueryCursor<C> for ($($fetch,)+) {
+            type Item = (Entity<C>, $($fetch::Item),+);
+
+            #[allow(non_snake_case)]
+            unsafe fn fetch(&self, entity: Entity<C>) -> Option<Self::Item> {
+                let ($($fetch,)+) = self;
+                $(let $fetch = $fetch.fetch(&entity)?;)+
+                Some((entity, $($fetch),+))
+            }
+        }
+    };
+}
+
+impl_query!(A);
+impl_query!(A, B);
+impl_query!(A, B, D);
+impl_query!(A, B, D, E);
+impl_query!(A, B, D, E, F);
+impl_query!(A, B, D, E, F, G);
+impl_query!(A, B, D, E, F, G, H);
+impl_query!(A, B, D, E, F, G, H, I);
+
+pub struct QueryIter<'a, C: Cont, Q: Query<C>, F: Filter = ()> {
+    entities: Iter<'a, C>,
+    cursor: Q::Cursor,
+    filter: F,
+}
+
+impl<'a, C: Cont, Q: Query<C>, F: Filter> QueryIter<'a, C, Q, F> {
+    pub(super) fn new(entities: Iter<'a, C>, cursor: Q::Cursor, filter: F) -> Self {
+        Self {
+            entities,
+            cursor,
+            filter,
+        }
+    }
+}
+
+impl<'a, C: Cont, Q: Query<C>, F: Filter> Iterator for QueryIter<'a, C, Q, F> {
+    type Item = Q::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let id = self.entities.position();
+            let word = id / TAG_WORD_BITS;
+            let mask = self.filter.mask(word) >> (id % TAG_WORD_BITS);
+            if mask == 0 {
+                if !self.entities.skip_to((word + 1) * TAG_WORD_BITS) {
+                    return None;
+                }
+                continue;
+            }
+            self.entities.skip_to(id + mask.trailing_zeros() as usize);
+
+            //  `Iter` visits every slot at most once, so each entity is only fetched once.
+            if let Some(entity) = self.entities.step()? {
+                if let Some(item) = unsafe { self.cursor.fetch(entity) } {
+                    return Some(item);
+                }
+            }
+        }
+    }
+}
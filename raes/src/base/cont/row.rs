@@ -1,40 +1,190 @@
 use super::*;
+use crate::base::Flushable;
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt,
+    marker::PhantomData,
+    ops::{Deref, DerefMut},
+};
 
-#[derive(Debug, Clone, Copy)]
+/// Per-entity data of a container, indexed by [`Entity::id`].
+///
+/// With a fixed `MAX_ENTITIES`, every slot is allocated upfront.
+/// With [`GROWABLE`], slots are allocated as the row's [`ContEntities`] grows.
+/// `#[derive(Cont)]` containers grow their rows on every spawn. Standalone rows allocate on
+/// first write, and [`Row::grow_to_fit`] brings them up to the entity table's capacity.
+/// Slots that were not allocated yet read as the row's initial value.
+///
+/// With [`Row::with_change_tracking`], every mutable access stamps the slot with the row's current
+/// tick, and [`Flushable::flush`] advances the tick at frame boundaries.
+/// Queries hand out [`Mut`]s, which only stamp the slot once they are actually written through.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "RawRow<T>")]
 pub struct Row<T, const MAX_ENTITIES: usize = DEFAULT_MAX_ENTITIES> {
-    datas: [T; MAX_ENTITIES],
+    datas: Vec<T>,
+    init_val: T,
+    /// Tick of the last mutable access to each slot, if change tracking is enabled.
+    /// Always as long as `datas`.
+    changes: Option<Vec<u64>>,
+    tick: u64,
+}
+
+/// The serialized form of a [`Row`], whose change ticks may not match its slots.
+#[derive(Deserialize)]
+struct RawRow<T> {
+    datas: Vec<T>,
+    init_val: T,
+    #[serde(default)]
+    changes: Option<Vec<u64>>,
+    #[serde(default = "first_tick")]
+    tick: u64,
+}
+
+impl<T, const MAX_ENTITIES: usize> From<RawRow<T>> for Row<T, MAX_ENTITIES> {
+    fn from(raw: RawRow<T>) -> Self {
+        let mut row = Self {
+            datas: raw.datas,
+            init_val: raw.init_val,
+            changes: raw.changes,
+            tick: raw.tick,
+        };
+        row.fit_changes();
+        row
+    }
+}
+
+//  Slots start at tick 0, so they only count as changed once actually written.
+fn first_tick() -> u64 {
+    1
 }
 
 impl<T, const MAX_ENTITIES: usize> Row<T, MAX_ENTITIES> {
+    /// Does not check whether `entity` is still alive, see [`Row::checked_get`].
     pub fn get<C: Cont>(&self, entity: Entity<C>) -> &T {
-        self.datas.get(entity.id()).unwrap_or_else(|| {
-            panic!(
-                "On get: exceeded max entities of {} for container {}, row type {}.",
-                MAX_ENTITIES,
-                std::any::type_name::<C>(),
-                std::any::type_name::<T>()
-            );
-        })
+        self.try_get(entity)
+            .unwrap_or_else(|e| panic!("On get: {}", e))
     }
 
-    pub fn get_mut<C: Cont>(&mut self, entity: Entity<C>) -> &mut T {
-        self.datas.get_mut(entity.id()).unwrap_or_else(|| {
-            panic!(
-                "On get_mut: exceeded max entities of {} for container {}, row type {}.",
-                MAX_ENTITIES,
-                std::any::type_name::<C>(),
-                std::any::type_name::<T>()
-            );
-        })
+    /// Like [`Row::get`], but returns [`ContError::OutOfRange`] instead of panicking.
+    pub fn try_get<C: Cont>(&self, entity: Entity<C>) -> Result<&T, ContError> {
+        self.check_range(&entity)?;
+        Ok(self.datas.get(entity.id()).unwrap_or(&self.init_val))
+    }
+
+    /// Like [`Row::try_get`], but also returns [`ContError::StaleHandle`] for entities that are
+    /// no longer alive in `entities` rather than reading a slot that may have been recycled.
+    pub fn checked_get<C: Cont, const CONT_MAX_ENTITIES: usize>(
+        &self,
+        entities: &ContEntities<C, CONT_MAX_ENTITIES>,
+        entity: Entity<C>,
+    ) -> Result<&T, ContError> {
+        entities.check_entity(&entity)?;
+        self.try_get(entity)
     }
 
+    /// Run `f` for every live entity of `entities` in parallel.
+    /// See [`ContEntities::par_query`].
+    pub fn par_for_each<C: Cont, const CONT_MAX_ENTITIES: usize>(
+        &self,
+        entities: &ContEntities<C, CONT_MAX_ENTITIES>,
+        f: impl Fn(Entity<C>, &T) + Send + Sync,
+    ) where
+        T: Sync,
+    {
+        entities.par_query((self,), |(entity, data)| f(entity, data));
+    }
+
+    /// With [`GROWABLE`], only covers the slots allocated so far, see [`Row::grow_to_fit`].
     pub fn as_slice(&self) -> &[T] {
         &self.datas
     }
 
+    /// Marks every slot as changed when change tracking is enabled.
     pub fn as_mut_slice(&mut self) -> &mut [T] {
+        if let Some(changes) = &mut self.changes {
+            changes.fill(self.tick);
+        }
         &mut self.datas
     }
+
+    /// Enable change tracking.
+    pub fn with_change_tracking(mut self) -> Self {
+        self.changes = Some(vec![0; self.datas.len()]);
+        self
+    }
+
+    pub fn is_change_tracked(&self) -> bool {
+        self.changes.is_some()
+    }
+
+    /// The tick that mutable accesses are currently stamped with.
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    /// Give every slot a change tick, dropping ticks of slots that do not exist.
+    /// Missing ticks count as unchanged.
+    fn fit_changes(&mut self) {
+        if let Some(changes) = &mut self.changes {
+            changes.resize(self.datas.len(), 0);
+        }
+    }
+
+    fn check_range<C: Cont>(&self, entity: &Entity<C>) -> Result<(), ContError> {
+        if entity.id() >= MAX_ENTITIES {
+            Err(ContError::OutOfRange {
+                cont: std::any::type_name::<C>(),
+                row: std::any::type_name::<T>(),
+                id: entity.id(),
+                max: MAX_ENTITIES,
+            })?
+        }
+        Ok(())
+    }
+
+    pub fn mark_changed<C: Cont>(&mut self, entity: Entity<C>) {
+        if let Some(change) = self
+            .changes
+            .as_mut()
+            .and_then(|changes| changes.get_mut(entity.id()))
+        {
+            *change = self.tick;
+        }
+    }
+
+    /// Iterate over the live entities whose slot was mutably accessed at or after `tick`.
+    pub fn changed_since<'a, C: Cont, const CONT_MAX_ENTITIES: usize>(
+        &'a self,
+        entities: &'a ContEntities<C, CONT_MAX_ENTITIES>,
+        tick: u64,
+    ) -> impl Iterator<Item = Entity<C>> + 'a {
+        let changes = self.changes.as_ref().unwrap_or_else(|| {
+            panic!(
+                "On changed_since: change tracking is not enabled for row type {}.",
+                std::any::type_name::<T>()
+            )
+        });
+        entities.iter().filter(move |entity| {
+            changes
+                .get(entity.id())
+                .map(|&change| change >= tick)
+                .unwrap_or(false)
+        })
+    }
+
+    /// Iterate over the live entities changed since the last flush.
+    pub fn changed<'a, C: Cont, const CONT_MAX_ENTITIES: usize>(
+        &'a self,
+        entities: &'a ContEntities<C, CONT_MAX_ENTITIES>,
+    ) -> impl Iterator<Item = Entity<C>> + 'a {
+        self.changed_since(entities, self.tick)
+    }
+}
+
+impl<T, const MAX_ENTITIES: usize> Flushable for Row<T, MAX_ENTITIES> {
+    fn flush(&mut self) {
+        self.tick += 1;
+    }
 }
 
 impl<T, const MAX_ENTITIES: usize> Row<T, MAX_ENTITIES>
@@ -42,36 +192,292 @@ where
     T: Clone,
 {
     pub fn new(init_val: T) -> Self {
-        let datas = unsafe {
-            (0..MAX_ENTITIES)
-                .map(|_| init_val.clone())
-                .collect::<Vec<_>>()
-                .try_into()
-                .unwrap_unchecked()
+        let datas = if MAX_ENTITIES == GROWABLE {
+            vec![]
+        } else {
+            vec![init_val.clone(); MAX_ENTITIES]
         };
-        Self { datas }
+        Self {
+            datas,
+            init_val,
+            changes: None,
+            tick: first_tick(),
+        }
+    }
+
+    /// Does not check whether `entity` is still alive, see [`Row::checked_get_mut`].
+    pub fn get_mut<C: Cont>(&mut self, entity: Entity<C>) -> &mut T {
+        self.try_get_mut(entity)
+            .unwrap_or_else(|e| panic!("On get_mut: {}", e))
+    }
+
+    /// Like [`Row::get_mut`], but returns [`ContError::OutOfRange`] instead of panicking.
+    pub fn try_get_mut<C: Cont>(&mut self, entity: Entity<C>) -> Result<&mut T, ContError> {
+        self.check_range(&entity)?;
+        self.grow(entity.id() + 1);
+        self.mark_changed(entity);
+        Ok(&mut self.datas[entity.id()])
+    }
+
+    /// Run `f` for every live entity of `entities` in parallel.
+    /// See [`ContEntities::par_query`].
+    ///
+    /// With change tracking, every visited slot is stamped as changed.
+    pub fn par_for_each_mut<C: Cont, const CONT_MAX_ENTITIES: usize>(
+        &mut self,
+        entities: &ContEntities<C, CONT_MAX_ENTITIES>,
+        f: impl Fn(Entity<C>, &mut T) + Send + Sync,
+    ) where
+        T: Send,
+    {
+        entities.par_query((self,), |(entity, mut data)| f(entity, &mut data));
+    }
+
+    /// Like [`Row::try_get_mut`], but also returns [`ContError::StaleHandle`] for entities that
+    /// are no longer alive in `entities`.
+    pub fn checked_get_mut<C: Cont, const CONT_MAX_ENTITIES: usize>(
+        &mut self,
+        entities: &ContEntities<C, CONT_MAX_ENTITIES>,
+        entity: Entity<C>,
+    ) -> Result<&mut T, ContError> {
+        entities.check_entity(&entity)?;
+        self.try_get_mut(entity)
+    }
+
+    /// Write every `(id, value)` of a delta from [`RowSnapshot`].
+    pub fn apply_delta(&mut self, delta: &[(usize, T)]) {
+        for (id, data) in delta {
+            self.grow(id + 1);
+            self.datas[*id] = data.clone();
+            if let Some(changes) = &mut self.changes {
+                changes[*id] = self.tick;
+            }
+        }
+    }
+
+    /// Set the slot of `entity` back to the initial value.
+    pub fn reset<C: Cont>(&mut self, entity: Entity<C>) {
+        if let Some(data) = self.datas.get_mut(entity.id()) {
+            *data = self.init_val.clone();
+        }
+    }
+
+    /// Allocate a slot for every id of `entities`, so that [`Row::as_slice`] covers all of its
+    /// live entities.
+    pub fn grow_to_fit<C: Cont, const CONT_MAX_ENTITIES: usize>(
+        &mut self,
+        entities: &ContEntities<C, CONT_MAX_ENTITIES>,
+    ) {
+        self.grow(entities.capacity().min(MAX_ENTITIES));
+    }
+
+    /// Allocate slots up to `len`, filling them with the initial value.
+    pub fn grow(&mut self, len: usize) {
+        if len > self.datas.len() {
+            self.datas.resize(len, self.init_val.clone());
+            if let Some(changes) = &mut self.changes {
+                changes.resize(len, 0);
+            }
+        }
+    }
+}
+
+/// The slots of a [`Row`].
+#[derive(Debug, Clone)]
+pub struct RowSnapshot<T> {
+    datas: Vec<T>,
+    init_val: T,
+}
+
+impl<T> RowSnapshot<T> {
+    /// Slots that were never allocated hold the initial value.
+    fn get(&self, id: usize) -> &T {
+        self.datas.get(id).unwrap_or(&self.init_val)
+    }
+}
+
+impl<T> Delta for RowSnapshot<T>
+where
+    T: Clone + PartialEq,
+{
+    /// Every slot whose value differs, as `(id, new value)`.
+    type Delta = Vec<(usize, T)>;
+
+    fn delta(&self, newer: &Self) -> Self::Delta {
+        let len = self.datas.len().max(newer.datas.len());
+        (0..len)
+            .filter(|&id| self.get(id) != newer.get(id))
+            .map(|id| (id, newer.get(id).clone()))
+            .collect()
+    }
+}
+
+impl<T, const MAX_ENTITIES: usize> Snapshot for Row<T, MAX_ENTITIES>
+where
+    T: Clone,
+{
+    type State = RowSnapshot<T>;
+
+    fn snapshot(&self) -> Self::State {
+        RowSnapshot {
+            datas: self.datas.clone(),
+            init_val: self.init_val.clone(),
+        }
+    }
+
+    fn snapshot_into(&self, state: &mut Self::State) {
+        state.datas.clone_from(&self.datas);
+        state.init_val.clone_from(&self.init_val);
+    }
+
+    /// Every restored slot counts as changed when change tracking is enabled.
+    fn restore(&mut self, state: &Self::State) {
+        self.datas.clone_from(&state.datas);
+        if let Some(changes) = &mut self.changes {
+            changes.clear();
+            changes.resize(self.datas.len(), self.tick);
+        }
+    }
+}
+
+impl<'a, T, const MAX_ENTITIES: usize> Fetch for &'a Row<T, MAX_ENTITIES> {
+    type Item = &'a T;
+    type Cursor = RowCursor<'a, T, MAX_ENTITIES>;
+
+    fn cursor(self, _len: usize) -> Self::Cursor {
+        RowCursor { row: self }
+    }
+}
+
+impl<'a, T, const MAX_ENTITIES: usize> Fetch for &'a mut Row<T, MAX_ENTITIES>
+where
+    T: Clone,
+{
+    type Item = Mut<'a, T>;
+    type Cursor = RowCursorMut<'a, T, MAX_ENTITIES>;
+
+    fn cursor(self, len: usize) -> Self::Cursor {
+        //  Grow upfront so that the pointer stays valid for `'a`.
+        self.grow(len.min(MAX_ENTITIES));
+        //  The cursor writes the change tick of every slot it hands out.
+        self.fit_changes();
+        RowCursorMut {
+            datas: self.datas.as_mut_ptr(),
+            changes: self
+                .changes
+                .as_mut()
+                .map(|changes| changes.as_mut_ptr())
+                .unwrap_or(std::ptr::null_mut()),
+            tick: self.tick,
+            len: self.datas.len(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+pub struct RowCursor<'a, T, const MAX_ENTITIES: usize> {
+    row: &'a Row<T, MAX_ENTITIES>,
+}
+
+impl<'a, T, const MAX_ENTITIES: usize> Cursor for RowCursor<'a, T, MAX_ENTITIES> {
+    type Item = &'a T;
+
+    unsafe fn fetch<C: Cont>(&self, entity: &Entity<C>) -> Option<Self::Item> {
+        let id = entity.id();
+        if id >= MAX_ENTITIES {
+            panic!(
+                "On query: exceeded max entities of {}, row type {}.",
+                MAX_ENTITIES,
+                std::any::type_name::<T>()
+            );
+        }
+        Some(self.row.datas.get(id).unwrap_or(&self.row.init_val))
+    }
+}
+
+pub struct RowCursorMut<'a, T, const MAX_ENTITIES: usize> {
+    datas: *mut T,
+    /// Null when change tracking is disabled.
+    changes: *mut u64,
+    tick: u64,
+    len: usize,
+    _phantom: PhantomData<&'a mut T>,
+}
+
+//  Sharing the cursor only ever hands out disjoint `&mut T`s, which is sound as long as `T` may be
+//  sent between threads.
+unsafe impl<'a, T: Send, const MAX_ENTITIES: usize> Send for RowCursorMut<'a, T, MAX_ENTITIES> {}
+unsafe impl<'a, T: Send, const MAX_ENTITIES: usize> Sync for RowCursorMut<'a, T, MAX_ENTITIES> {}
+
+impl<'a, T, const MAX_ENTITIES: usize> Cursor for RowCursorMut<'a, T, MAX_ENTITIES> {
+    type Item = Mut<'a, T>;
+
+    unsafe fn fetch<C: Cont>(&self, entity: &Entity<C>) -> Option<Self::Item> {
+        let id = entity.id();
+        if id >= MAX_ENTITIES {
+            panic!(
+                "On query: exceeded max entities of {}, row type {}.",
+                MAX_ENTITIES,
+                std::any::type_name::<T>()
+            );
+        }
+        if id >= self.len {
+            None?
+        }
+        //  The caller guarantees that `id` is only handed out once.
+        Some(Mut {
+            data: &mut *self.datas.add(id),
+            change: (!self.changes.is_null()).then(|| &mut *self.changes.add(id)),
+            tick: self.tick,
+        })
+    }
+}
+
+/// A mutable query item of a [`Row`].
+///
+/// With change tracking, the slot is only stamped as changed once it is mutably dereferenced,
+/// so entities that a query visits but does not write are not reported by [`Row::changed`].
+pub struct Mut<'a, T> {
+    data: &'a mut T,
+    change: Option<&'a mut u64>,
+    tick: u64,
+}
+
+impl<'a, T> Deref for Mut<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        self.data
+    }
+}
+
+impl<'a, T> DerefMut for Mut<'a, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        if let Some(change) = &mut self.change {
+            **change = self.tick;
+        }
+        self.data
+    }
+}
+
+impl<'a, T: fmt::Debug> fmt::Debug for Mut<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.data.fmt(f)
     }
 }
 
 impl<T, const MAX_ENTITIES: usize> Row<T, MAX_ENTITIES>
 where
-    T: Default,
+    T: Clone + Default,
 {
     pub fn new_with_default() -> Self {
-        let datas = unsafe {
-            (0..MAX_ENTITIES)
-                .map(|_| T::default())
-                .collect::<Vec<_>>()
-                .try_into()
-                .unwrap_unchecked()
-        };
-        Self { datas }
+        Self::new(T::default())
     }
 }
 
 impl<T, const MAX_ENTITIES: usize> Default for Row<T, MAX_ENTITIES>
 where
-    T: Default,
+    T: Clone + Default,
 {
     fn default() -> Self {
         Self::new_with_default()
@@ -0,0 +1,128 @@
+use std::collections::VecDeque;
+
+/// State that can be captured and later restored, such as a container or one of its rows.
+pub trait Snapshot {
+    type State: Clone;
+
+    fn snapshot(&self) -> Self::State;
+
+    /// Overwrite `state` with a new snapshot, reusing its allocations where possible.
+    fn snapshot_into(&self, state: &mut Self::State) {
+        *state = self.snapshot();
+    }
+
+    fn restore(&mut self, state: &Self::State);
+}
+
+/// Snapshot states that can describe what changed between two of them.
+pub trait Delta {
+    type Delta;
+
+    fn delta(&self, newer: &Self) -> Self::Delta;
+}
+
+macro_rules! impl_delta {
+    ($($state:ident $index:tt),+) => {
+        impl<$($state: Delta),+> Delta for ($($state,)+) {
+            type Delta = ($($state::Delta,)+);
+
+            fn delta(&self, newer: &Self) -> Self::Delta {
+                ($(self.$index.delta(&newer.$index),)+)
+            }
+        }
+    };
+}
+
+impl_delta!(A 0);
+impl_delta!(A 0, B 1);
+impl_delta!(A 0, B 1, D 2);
+impl_delta!(A 0, B 1, D 2, E 3);
+impl_delta!(A 0, B 1, D 2, E 3, F 4);
+impl_delta!(A 0, B 1, D 2, E 3, F 4, G 5);
+impl_delta!(A 0, B 1, D 2, E 3, F 4, G 5, H 6);
+impl_delta!(A 0, B 1, D 2, E 3, F 4, G 5, H 6, I 7);
+impl_delta!(A 0, B 1, D 2, E 3, F 4, G 5, H 6, I 7, J 8);
+impl_delta!(A 0, B 1, D 2, E 3, F 4, G 5, H 6, I 7, J 8, K 9);
+impl_delta!(A 0, B 1, D 2, E 3, F 4, G 5, H 6, I 7, J 8, K 9, L 10);
+impl_delta!(A 0, B 1, D 2, E 3, F 4, G 5, H 6, I 7, J 8, K 9, L 10, M 11);
+
+/// A ring buffer of the snapshots of the last `capacity` frames, for rollback netcode or
+/// rewinding time.
+///
+/// Once full, saving a frame overwrites the oldest snapshot in place.
+pub struct Rollback<S: Snapshot> {
+    frames: VecDeque<(u64, S::State)>,
+    capacity: usize,
+}
+
+impl<S: Snapshot> Rollback<S> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            frames: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Frames should be saved in increasing order.
+    pub fn save(&mut self, frame: u64, source: &S) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        if self.frames.len() == self.capacity {
+            let (_, mut state) = self.frames.pop_front().unwrap();
+            source.snapshot_into(&mut state);
+            self.frames.push_back((frame, state));
+        } else {
+            self.frames.push_back((frame, source.snapshot()));
+        }
+    }
+
+    pub fn get(&self, frame: u64) -> Option<&S::State> {
+        self.frames
+            .iter()
+            .find(|(saved_frame, _)| *saved_frame == frame)
+            .map(|(_, state)| state)
+    }
+
+    /// Restore `target` to `frame`, discarding every later snapshot.
+    /// Returns `false` if `frame` is no longer buffered.
+    pub fn rewind(&mut self, frame: u64, target: &mut S) -> bool {
+        let Some(state) = self.get(frame) else {
+            return false;
+        };
+        target.restore(state);
+        while matches!(self.frames.back(), Some(&(saved_frame, _)) if saved_frame > frame) {
+            self.frames.pop_back();
+        }
+        true
+    }
+
+    /// What changed between the snapshots of two frames, if both are still buffered.
+    pub fn delta(&self, from: u64, to: u64) -> Option<<S::State as Delta>::Delta>
+    where
+        S::State: Delta,
+    {
+        Some(self.get(from)?.delta(self.get(to)?))
+    }
+
+    pub fn oldest_frame(&self) -> Option<u64> {
+        self.frames.front().map(|&(frame, _)| frame)
+    }
+
+    pub fn latest_frame(&self) -> Option<u64> {
+        self.frames.back().map(|&(frame, _)| frame)
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.frames.clear();
+    }
+}
@@ -0,0 +1,322 @@
+use super::*;
+use serde::{Deserialize, Serialize};
+use std::marker::PhantomData;
+
+/// Per-entity data that only some entities have.
+///
+/// Unlike [`Row`], memory is only used for entities that were inserted.
+/// Values are packed densely, so iteration only visits present values.
+/// In a [`ContEntities::query`], entities without a value are skipped.
+///
+/// Deserializing checks that every entity id maps to its own value, since mutable queries rely
+/// on it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "RawSparseRow<T>")]
+pub struct SparseRow<T> {
+    /// Entity id to index into `keys` and `datas`.
+    sparse: Vec<Option<usize>>,
+    /// `(id, generation)` of the entity owning each value.
+    keys: Vec<(usize, usize)>,
+    datas: Vec<T>,
+}
+
+/// The serialized form of a [`SparseRow`], before it is validated.
+#[derive(Deserialize)]
+struct RawSparseRow<T> {
+    sparse: Vec<Option<usize>>,
+    keys: Vec<(usize, usize)>,
+    datas: Vec<T>,
+}
+
+impl<T> TryFrom<RawSparseRow<T>> for SparseRow<T> {
+    type Error = String;
+
+    fn try_from(raw: RawSparseRow<T>) -> Result<Self, Self::Error> {
+        let row = Self {
+            sparse: raw.sparse,
+            keys: raw.keys,
+            datas: raw.datas,
+        };
+        row.validate()?;
+        Ok(row)
+    }
+}
+
+impl<T> SparseRow<T> {
+    pub fn new() -> Self {
+        Self {
+            sparse: vec![],
+            keys: vec![],
+            datas: vec![],
+        }
+    }
+
+    /// Returns the previous value of `entity`, if any.
+    pub fn insert<C: Cont>(&mut self, entity: Entity<C>, data: T) -> Option<T> {
+        let id = entity.id();
+        if let Some(index) = self.sparse.get(id).copied().flatten() {
+            let old_generation = std::mem::replace(&mut self.keys[index].1, entity.generation());
+            let old = std::mem::replace(&mut self.datas[index], data);
+            return (old_generation == entity.generation()).then_some(old);
+        }
+
+        if id >= self.sparse.len() {
+            self.sparse.resize(id + 1, None);
+        }
+        self.sparse[id] = Some(self.datas.len());
+        self.keys.push((id, entity.generation()));
+        self.datas.push(data);
+        None
+    }
+
+    pub fn remove<C: Cont>(&mut self, entity: Entity<C>) -> Option<T> {
+        let index = self.index_of(&entity)?;
+        Some(self.remove_at(entity.id(), index))
+    }
+
+    /// Remove the value in the slot of `entity`, even one inserted for an earlier generation.
+    pub fn reset<C: Cont>(&mut self, entity: Entity<C>) {
+        if let Some(index) = self.sparse.get(entity.id()).copied().flatten() {
+            self.remove_at(entity.id(), index);
+        }
+    }
+
+    fn remove_at(&mut self, id: usize, index: usize) -> T {
+        self.sparse[id] = None;
+        self.keys.swap_remove(index);
+        let data = self.datas.swap_remove(index);
+        if let Some(&(moved_id, _)) = self.keys.get(index) {
+            self.sparse[moved_id] = Some(index);
+        }
+        data
+    }
+
+    pub fn get<C: Cont>(&self, entity: Entity<C>) -> Option<&T> {
+        self.index_of(&entity).map(|index| &self.datas[index])
+    }
+
+    pub fn get_mut<C: Cont>(&mut self, entity: Entity<C>) -> Option<&mut T> {
+        self.index_of(&entity).map(|index| &mut self.datas[index])
+    }
+
+    pub fn contains<C: Cont>(&self, entity: Entity<C>) -> bool {
+        self.index_of(&entity).is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.datas.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.datas.is_empty()
+    }
+
+    /// Iterate over every stored value and its owner.
+    ///
+    /// Owners are not checked against a [`ContEntities`], so values of despawned entities are
+    /// included until removed.
+    pub fn iter<C: Cont>(&self) -> impl Iterator<Item = (Entity<C>, &T)> {
+        self.keys
+            .iter()
+            .zip(self.datas.iter())
+            .map(|(&(id, generation), data)| (Entity::new(id, generation), data))
+    }
+
+    pub fn iter_mut<C: Cont>(&mut self) -> impl Iterator<Item = (Entity<C>, &mut T)> {
+        self.keys
+            .iter()
+            .zip(self.datas.iter_mut())
+            .map(|(&(id, generation), data)| (Entity::new(id, generation), data))
+    }
+
+    pub fn as_slice(&self) -> &[T] {
+        &self.datas
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        &mut self.datas
+    }
+
+    pub fn clear(&mut self) {
+        self.sparse.clear();
+        self.keys.clear();
+        self.datas.clear();
+    }
+
+    fn index_of<C: Cont>(&self, entity: &Entity<C>) -> Option<usize> {
+        index_of(&self.sparse, &self.keys, entity)
+    }
+
+    /// Check that `sparse` and `keys` map each id to its own value and back.
+    fn validate(&self) -> Result<(), String> {
+        let row = std::any::type_name::<T>();
+        if self.keys.len() != self.datas.len() {
+            Err(format!(
+                "sparse row of {} has {} keys for {} values.",
+                row,
+                self.keys.len(),
+                self.datas.len()
+            ))?
+        }
+
+        let mut used = vec![false; self.datas.len()];
+        for (id, index) in self.sparse.iter().enumerate() {
+            let Some(index) = *index else {
+                continue;
+            };
+            match self.keys.get(index) {
+                None => Err(format!(
+                    "index {} of id {} in sparse row of {} is out of range of {} values.",
+                    index,
+                    id,
+                    row,
+                    self.datas.len()
+                ))?,
+                Some(_) if used[index] => Err(format!(
+                    "index {} in sparse row of {} is used twice.",
+                    index, row
+                ))?,
+                Some(&(key, _)) if key != id => Err(format!(
+                    "index {} of id {} in sparse row of {} is keyed by id {}.",
+                    index, id, row, key
+                ))?,
+                Some(_) => used[index] = true,
+            }
+        }
+
+        if let Some(index) = used.iter().position(|&used| !used) {
+            Err(format!(
+                "value {} in sparse row of {} has no id.",
+                index, row
+            ))?
+        }
+        Ok(())
+    }
+}
+
+impl<T> Default for SparseRow<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone> Snapshot for SparseRow<T> {
+    type State = Self;
+
+    fn snapshot(&self) -> Self::State {
+        self.clone()
+    }
+
+    fn snapshot_into(&self, state: &mut Self::State) {
+        state.clone_from(self);
+    }
+
+    fn restore(&mut self, state: &Self::State) {
+        self.clone_from(state);
+    }
+}
+
+impl<T> Delta for SparseRow<T>
+where
+    T: Clone + PartialEq,
+{
+    /// Every changed value as `(id, generation, new value)`, where removed values are `None`.
+    type Delta = Vec<(usize, usize, Option<T>)>;
+
+    fn delta(&self, newer: &Self) -> Self::Delta {
+        let removed = self
+            .keys
+            .iter()
+            .filter(|&&(id, generation)| {
+                index_of_key(&newer.sparse, &newer.keys, id, generation).is_none()
+            })
+            .map(|&(id, generation)| (id, generation, None));
+        let changed = newer
+            .keys
+            .iter()
+            .zip(newer.datas.iter())
+            .filter(|&(&(id, generation), data)| {
+                index_of_key(&self.sparse, &self.keys, id, generation)
+                    .map(|index| &self.datas[index] != data)
+                    .unwrap_or(true)
+            })
+            .map(|(&(id, generation), data)| (id, generation, Some(data.clone())));
+        removed.chain(changed).collect()
+    }
+}
+
+impl<'a, T> Fetch for &'a SparseRow<T> {
+    type Item = &'a T;
+    type Cursor = SparseRowCursor<'a, T>;
+
+    fn cursor(self, _len: usize) -> Self::Cursor {
+        SparseRowCursor { row: self }
+    }
+}
+
+impl<'a, T> Fetch for &'a mut SparseRow<T> {
+    type Item = &'a mut T;
+    type Cursor = SparseRowCursorMut<'a, T>;
+
+    fn cursor(self, _len: usize) -> Self::Cursor {
+        SparseRowCursorMut {
+            sparse: &self.sparse,
+            keys: &self.keys,
+            datas: self.datas.as_mut_ptr(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+pub struct SparseRowCursor<'a, T> {
+    row: &'a SparseRow<T>,
+}
+
+impl<'a, T> Cursor for SparseRowCursor<'a, T> {
+    type Item = &'a T;
+
+    unsafe fn fetch<C: Cont>(&self, entity: &Entity<C>) -> Option<Self::Item> {
+        self.row
+            .index_of(entity)
+            .map(|index| &self.row.datas[index])
+    }
+}
+
+pub struct SparseRowCursorMut<'a, T> {
+    sparse: &'a [Option<usize>],
+    keys: &'a [(usize, usize)],
+    datas: *mut T,
+    _phantom: PhantomData<&'a mut T>,
+}
+
+//  See `RowCursorMut`.
+unsafe impl<'a, T: Send> Send for SparseRowCursorMut<'a, T> {}
+unsafe impl<'a, T: Send> Sync for SparseRowCursorMut<'a, T> {}
+
+impl<'a, T> Cursor for SparseRowCursorMut<'a, T> {
+    type Item = &'a mut T;
+
+    unsafe fn fetch<C: Cont>(&self, entity: &Entity<C>) -> Option<Self::Item> {
+        //  Each entity maps to its own index and is only fetched once,
+        //  so the returned references never alias.
+        index_of(self.sparse, self.keys, entity).map(|index| &mut *self.datas.add(index))
+    }
+}
+
+fn index_of<C: Cont>(
+    sparse: &[Option<usize>],
+    keys: &[(usize, usize)],
+    entity: &Entity<C>,
+) -> Option<usize> {
+    index_of_key(sparse, keys, entity.id(), entity.generation())
+}
+
+fn index_of_key(
+    sparse: &[Option<usize>],
+    keys: &[(usize, usize)],
+    id: usize,
+    generation: usize,
+) -> Option<usize> {
+    let index = sparse.get(id).copied().flatten()?;
+    (keys[index] == (id, generation)).then_some(index)
+}
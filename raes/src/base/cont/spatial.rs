@@ -0,0 +1,332 @@
+use super::*;
+use cgmath::{InnerSpace, Vector2};
+use std::collections::HashMap;
+
+type Cell = (i32, i32);
+
+#[derive(Debug, Clone)]
+struct Slot<C: Cont> {
+    entity: Entity<C>,
+    position: Vector2<f32>,
+    cell: Cell,
+}
+
+/// A uniform grid over the 2D positions of a container's entities, for proximity queries that
+/// would otherwise scan every slot of a position [`Row`].
+///
+/// Keep it in sync with [`SpatialGrid::sync`] once per frame, or move single entities with
+/// [`SpatialGrid::insert`] and [`SpatialGrid::remove`].
+/// Pick a cell size around the typical query radius.
+#[derive(Debug, Clone)]
+pub struct SpatialGrid<C: Cont> {
+    cell_size: f32,
+    cells: HashMap<Cell, Vec<Entity<C>>>,
+    /// Indexed entity, position and cell of each entity id.
+    slots: Vec<Option<Slot<C>>>,
+    len: usize,
+    /// Row tick of the last [`SpatialGrid::sync`].
+    synced_tick: u64,
+    /// Whether a [`SpatialGrid::sync`] ran since the grid was created or cleared.
+    synced: bool,
+}
+
+impl<C: Cont> SpatialGrid<C> {
+    pub fn new(cell_size: f32) -> Self {
+        assert!(
+            cell_size > 0.0,
+            "On new: cell size of {} for spatial grid of container {} must be positive.",
+            cell_size,
+            std::any::type_name::<C>()
+        );
+        Self {
+            cell_size,
+            cells: HashMap::new(),
+            slots: vec![],
+            len: 0,
+            synced_tick: 0,
+            synced: false,
+        }
+    }
+
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+
+    /// Index `entity` at `position`, moving it if it was already indexed.
+    /// An entity of an earlier generation in the same slot is replaced.
+    pub fn insert(&mut self, entity: Entity<C>, position: Vector2<f32>) {
+        let cell = self.cell_of(position);
+        let id = entity.id();
+        if id >= self.slots.len() {
+            self.slots.resize_with(id + 1, || None);
+        }
+
+        match self.slots[id].replace(Slot {
+            entity,
+            position,
+            cell,
+        }) {
+            Some(old) if old.entity == entity && old.cell == cell => return,
+            Some(old) => self.remove_from_cell(old.entity, old.cell),
+            None => self.len += 1,
+        }
+        self.cells.entry(cell).or_default().push(entity);
+    }
+
+    /// Returns the indexed position of `entity`, if any.
+    pub fn remove(&mut self, entity: Entity<C>) -> Option<Vector2<f32>> {
+        let slot = self.slots.get_mut(entity.id())?;
+        if slot.as_ref()?.entity != entity {
+            None?
+        }
+        let Slot { cell, position, .. } = slot.take()?;
+        self.len -= 1;
+        self.remove_from_cell(entity, cell);
+        Some(position)
+    }
+
+    pub fn position(&self, entity: Entity<C>) -> Option<Vector2<f32>> {
+        self.slot(entity).map(|slot| slot.position)
+    }
+
+    pub fn contains(&self, entity: Entity<C>) -> bool {
+        self.slot(entity).is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.clear();
+        self.slots.clear();
+        self.len = 0;
+        self.synced_tick = 0;
+        self.synced = false;
+    }
+
+    /// Bring the grid up to date with `positions`: index every live entity at its position and
+    /// drop despawned ones.
+    ///
+    /// With [`ContEntities::buffer_lifecycle`], only the entities spawned and despawned since the
+    /// last flush are visited, so sync once per frame before flushing the container. Otherwise,
+    /// or once a lifecycle buffer dropped entities, every indexed entity is checked.
+    ///
+    /// With [`Row::with_change_tracking`], only entities changed since the last sync are
+    /// re-indexed. Otherwise every live entity is.
+    pub fn sync<const MAX_ENTITIES: usize, const ROW_MAX_ENTITIES: usize>(
+        &mut self,
+        entities: &ContEntities<C, MAX_ENTITIES>,
+        positions: &Row<Vector2<f32>, ROW_MAX_ENTITIES>,
+    ) {
+        let lifecycle = match (entities.spawned(), entities.despawned()) {
+            (Some(spawned), Some(despawned))
+                if self.synced && spawned.dropped() == 0 && despawned.dropped() == 0 =>
+            {
+                Some((spawned, despawned))
+            }
+            _ => None,
+        };
+        self.synced = true;
+
+        if let Some((_, despawned)) = lifecycle {
+            for &entity in despawned.iter() {
+                self.remove(entity);
+            }
+        } else {
+            let despawned = self
+                .slots
+                .iter()
+                .flatten()
+                .map(|slot| slot.entity)
+                .filter(|entity| !entities.has_entity(entity))
+                .collect::<Vec<_>>();
+            for entity in despawned {
+                self.remove(entity);
+            }
+        }
+
+        if positions.is_change_tracked() {
+            for entity in positions.changed_since(entities, self.synced_tick) {
+                self.insert(entity, *positions.get(entity));
+            }
+            if let Some((spawned, _)) = lifecycle {
+                for &entity in spawned.iter() {
+                    if entities.has_entity(&entity) && !self.contains(entity) {
+                        self.insert(entity, *positions.get(entity));
+                    }
+                }
+            } else {
+                for entity in entities.iter() {
+                    if !self.contains(entity) {
+                        self.insert(entity, *positions.get(entity));
+                    }
+                }
+            }
+            self.synced_tick = positions.tick();
+        } else {
+            for entity in entities.iter() {
+                self.insert(entity, *positions.get(entity));
+            }
+        }
+    }
+
+    /// Every indexed entity within `radius` of `center`.
+    pub fn query_radius(
+        &self,
+        center: Vector2<f32>,
+        radius: f32,
+    ) -> impl Iterator<Item = Entity<C>> + '_ {
+        let extent = Vector2::new(radius, radius);
+        let radius2 = radius * radius;
+        self.query_cells(center - extent, center + extent)
+            .filter(move |&(_, position)| (position - center).magnitude2() <= radius2)
+            .map(|(entity, _)| entity)
+    }
+
+    /// Every indexed entity inside the axis-aligned box from `min` to `max`, inclusive.
+    pub fn query_aabb(
+        &self,
+        min: Vector2<f32>,
+        max: Vector2<f32>,
+    ) -> impl Iterator<Item = Entity<C>> + '_ {
+        self.query_cells(min, max)
+            .filter(move |&(_, position)| {
+                position.x >= min.x
+                    && position.y >= min.y
+                    && position.x <= max.x
+                    && position.y <= max.y
+            })
+            .map(|(entity, _)| entity)
+    }
+
+    /// The indexed entity closest to `point`, if any.
+    ///
+    /// Rings of cells around `point` are visited outwards while they are smaller than the occupied
+    /// cells. Past that, the remaining occupied cells are visited directly, so far away entities
+    /// stay cheap to find.
+    pub fn nearest(&self, point: Vector2<f32>) -> Option<Entity<C>> {
+        let center = self.cell_of(point);
+        //  Squared distances are kept in `f64`, since far apart `f32` positions overflow them.
+        let mut best: Option<(Entity<C>, f64)> = None;
+        let visit = |best: &mut Option<(Entity<C>, f64)>, occupants: &Vec<Entity<C>>| {
+            for &entity in occupants {
+                let offset = self.slots[entity.id()].as_ref().unwrap().position - point;
+                let distance2 = (offset.x as f64).powi(2) + (offset.y as f64).powi(2);
+                if best.map(|(_, best)| distance2 < best).unwrap_or(true) {
+                    *best = Some((entity, distance2));
+                }
+            }
+        };
+
+        let mut ring = 0;
+        loop {
+            if let Some((_, best_distance2)) = best {
+                //  Every point in this ring is at least this far from `point`.
+                let ring_distance = (ring - 1) as f64 * self.cell_size as f64;
+                if ring_distance * ring_distance > best_distance2 {
+                    return best.map(|(entity, _)| entity);
+                }
+            }
+            //  Cells within `ring`, which stop paying off once they outnumber the occupied cells.
+            let area = (2 * ring + 1) * (2 * ring + 1);
+            if area > self.cells.len() as i64 {
+                break;
+            }
+
+            for cell in ring_cells(center, ring) {
+                if let Some(occupants) = self.cells.get(&cell) {
+                    visit(&mut best, occupants);
+                }
+            }
+            ring += 1;
+        }
+
+        self.cells
+            .iter()
+            .filter(|&(&cell, _)| chebyshev(center, cell) >= ring)
+            .for_each(|(_, occupants)| visit(&mut best, occupants));
+        best.map(|(entity, _)| entity)
+    }
+
+    /// Occupants of the cells overlapping `min..=max`, visiting whichever is smaller of the cell
+    /// range and the occupied cells, so that huge or infinite ranges stay cheap.
+    fn query_cells(
+        &self,
+        min: Vector2<f32>,
+        max: Vector2<f32>,
+    ) -> impl Iterator<Item = (Entity<C>, Vector2<f32>)> + '_ {
+        let (min_x, min_y) = self.cell_of(min);
+        let (max_x, max_y) = self.cell_of(max);
+        let width = (max_x as i64 - min_x as i64 + 1).max(0);
+        let height = (max_y as i64 - min_y as i64 + 1).max(0);
+        let scan_range = width.saturating_mul(height) <= self.cells.len() as i64;
+
+        let range = scan_range.then(|| {
+            (min_x..=max_x)
+                .flat_map(move |x| (min_y..=max_y).map(move |y| (x, y)))
+                .filter_map(|cell| self.cells.get(&cell))
+        });
+        let occupied = (!scan_range).then(|| {
+            self.cells
+                .iter()
+                .filter(move |&(&(x, y), _)| x >= min_x && y >= min_y && x <= max_x && y <= max_y)
+                .map(|(_, occupants)| occupants)
+        });
+        range
+            .into_iter()
+            .flatten()
+            .chain(occupied.into_iter().flatten())
+            .flatten()
+            .map(|&entity| (entity, self.slots[entity.id()].as_ref().unwrap().position))
+    }
+
+    fn slot(&self, entity: Entity<C>) -> Option<&Slot<C>> {
+        self.slots
+            .get(entity.id())?
+            .as_ref()
+            .filter(|slot| slot.entity == entity)
+    }
+
+    fn remove_from_cell(&mut self, entity: Entity<C>, cell: Cell) {
+        if let Some(occupants) = self.cells.get_mut(&cell) {
+            occupants.retain(|&occupant| occupant != entity);
+            if occupants.is_empty() {
+                self.cells.remove(&cell);
+            }
+        }
+    }
+
+    fn cell_of(&self, position: Vector2<f32>) -> Cell {
+        (
+            (position.x / self.cell_size).floor() as i32,
+            (position.y / self.cell_size).floor() as i32,
+        )
+    }
+}
+
+/// Cells at Chebyshev distance `ring` from `center`, leaving out those past the edges of the grid.
+fn ring_cells((cx, cy): Cell, ring: i64) -> impl Iterator<Item = Cell> {
+    let (cx, cy) = (cx as i64, cy as i64);
+    //  Ring 0 is a single cell, whose top and bottom row are the same.
+    let edges = if ring == 0 { 1 } else { 2 };
+    let rows = (-ring..=ring).flat_map(move |dx| {
+        [(cx + dx, cy - ring), (cx + dx, cy + ring)]
+            .into_iter()
+            .take(edges)
+    });
+    let columns =
+        (-ring + 1..ring).flat_map(move |dy| [(cx - ring, cy + dy), (cx + ring, cy + dy)]);
+    rows.chain(columns)
+        .filter_map(|(x, y)| Some((i32::try_from(x).ok()?, i32::try_from(y).ok()?)))
+}
+
+fn chebyshev((ax, ay): Cell, (bx, by): Cell) -> i64 {
+    (ax as i64 - bx as i64)
+        .abs()
+        .max((ay as i64 - by as i64).abs())
+}
@@ -0,0 +1,95 @@
+use super::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// An identifier that stays the same across save/load and network boundaries,
+/// unlike the slot id of an [`Entity`].
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct StableId(pub u64);
+
+/// A two way mapping between [`StableId`]s and the entities of a container.
+///
+/// Ids are never reused, even after [`StableIds::remove`].
+/// After entities are respawned into different slots, [`StableIds::bind`] points an existing id at
+/// its new entity.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct StableIds<C: Cont> {
+    next_id: u64,
+    /// Set once `StableId(u64::MAX)` was handed out or bound.
+    #[serde(default)]
+    exhausted: bool,
+    to_entity: HashMap<StableId, Entity<C>>,
+    to_stable: HashMap<Entity<C>, StableId>,
+}
+
+impl<C: Cont> StableIds<C> {
+    pub fn new() -> Self {
+        Self {
+            next_id: 0,
+            exhausted: false,
+            to_entity: HashMap::new(),
+            to_stable: HashMap::new(),
+        }
+    }
+
+    /// Get the id of `entity`, creating one if it has none.
+    /// Panics once every id was handed out.
+    pub fn assign(&mut self, entity: Entity<C>) -> StableId {
+        if let Some(&stable) = self.to_stable.get(&entity) {
+            return stable;
+        }
+
+        if self.exhausted {
+            panic!(
+                "On assign: every stable id of container {} was handed out.",
+                std::any::type_name::<C>()
+            )
+        }
+        let stable = StableId(self.next_id);
+        self.advance_past(stable);
+        self.to_entity.insert(stable, entity);
+        self.to_stable.insert(entity, stable);
+        stable
+    }
+
+    /// Point `stable` at `entity`, replacing whatever either was previously mapped to.
+    pub fn bind(&mut self, stable: StableId, entity: Entity<C>) {
+        if let Some(old_entity) = self.to_entity.insert(stable, entity) {
+            self.to_stable.remove(&old_entity);
+        }
+        if let Some(old_stable) = self.to_stable.insert(entity, stable) {
+            if old_stable != stable {
+                self.to_entity.remove(&old_stable);
+            }
+        }
+        self.advance_past(stable);
+    }
+
+    fn advance_past(&mut self, stable: StableId) {
+        match stable.0.checked_add(1) {
+            Some(next_id) => self.next_id = self.next_id.max(next_id),
+            None => self.exhausted = true,
+        }
+    }
+
+    pub fn resolve(&self, stable: StableId) -> Option<Entity<C>> {
+        self.to_entity.get(&stable).copied()
+    }
+
+    pub fn stable_id(&self, entity: Entity<C>) -> Option<StableId> {
+        self.to_stable.get(&entity).copied()
+    }
+
+    pub fn remove(&mut self, entity: Entity<C>) -> Option<StableId> {
+        let stable = self.to_stable.remove(&entity)?;
+        self.to_entity.remove(&stable);
+        Some(stable)
+    }
+}
+
+impl<C: Cont> Default for StableIds<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
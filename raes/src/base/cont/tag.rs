@@ -0,0 +1,137 @@
+use super::*;
+use serde::{Deserialize, Serialize};
+
+/// A compact per-entity flag, such as "visible" or "frozen", stored as one bit per slot.
+///
+/// Like [`Row`], tags are indexed by [`Entity::id`] and do not check generations,
+/// so tags should be cleared on despawn.
+/// Use [`With`] and [`Without`] to filter a [`ContEntities::query_filtered`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TagRow {
+    words: Vec<u64>,
+}
+
+impl TagRow {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set<C: Cont>(&mut self, entity: Entity<C>) {
+        let (word, bit) = Self::locate(entity.id());
+        if word >= self.words.len() {
+            self.words.resize(word + 1, 0);
+        }
+        self.words[word] |= bit;
+    }
+
+    pub fn clear<C: Cont>(&mut self, entity: Entity<C>) {
+        let (word, bit) = Self::locate(entity.id());
+        if let Some(word) = self.words.get_mut(word) {
+            *word &= !bit;
+        }
+    }
+
+    pub fn test<C: Cont>(&self, entity: Entity<C>) -> bool {
+        let (word, bit) = Self::locate(entity.id());
+        self.word(word) & bit != 0
+    }
+
+    pub fn clear_all(&mut self) {
+        self.words.clear();
+    }
+
+    /// The number of set tags, including those of despawned entities that were never cleared.
+    pub fn count(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    fn word(&self, word: usize) -> u64 {
+        self.words.get(word).copied().unwrap_or(0)
+    }
+
+    fn locate(id: usize) -> (usize, u64) {
+        (id / TAG_WORD_BITS, 1 << (id % TAG_WORD_BITS))
+    }
+}
+
+impl Snapshot for TagRow {
+    type State = Self;
+
+    fn snapshot(&self) -> Self::State {
+        self.clone()
+    }
+
+    fn snapshot_into(&self, state: &mut Self::State) {
+        state.clone_from(self);
+    }
+
+    fn restore(&mut self, state: &Self::State) {
+        self.clone_from(state);
+    }
+}
+
+impl Delta for TagRow {
+    /// Every changed word as `(word index, new word)`, where word `i` holds entity ids
+    /// `i * 64..(i + 1) * 64`.
+    type Delta = Vec<(usize, u64)>;
+
+    fn delta(&self, newer: &Self) -> Self::Delta {
+        let len = self.words.len().max(newer.words.len());
+        (0..len)
+            .filter(|&word| self.word(word) != newer.word(word))
+            .map(|word| (word, newer.word(word)))
+            .collect()
+    }
+}
+
+/// Narrows the entities visited by a [`ContEntities::query_filtered`].
+pub trait Filter {
+    /// The bits of entity ids `word * 64..(word + 1) * 64` that pass.
+    fn mask(&self, word: usize) -> u64;
+}
+
+/// Only visit entities with the tag set.
+pub struct With<'a>(pub &'a TagRow);
+
+/// Only visit entities with the tag cleared.
+pub struct Without<'a>(pub &'a TagRow);
+
+impl Filter for () {
+    fn mask(&self, _word: usize) -> u64 {
+        !0
+    }
+}
+
+impl<'a> Filter for With<'a> {
+    fn mask(&self, word: usize) -> u64 {
+        self.0.word(word)
+    }
+}
+
+impl<'a> Filter for Without<'a> {
+    fn mask(&self, word: usize) -> u64 {
+        !self.0.word(word)
+    }
+}
+
+macro_rules! impl_filter {
+    ($($filter:ident),+) => {
+        impl<$($filter: Filter),+> Filter for ($($filter,)+) {
+            #[allow(non_snake_case)]
+            fn mask(&self, word: usize) -> u64 {
+                let ($($filter,)+) = self;
+                !0 $(& $filter.mask(word))+
+            }
+        }
+    };
+}
+
+impl_filter!(A);
+impl_filter!(A, B);
+impl_filter!(A, B, D);
+impl_filter!(A, B, D, E);
+impl_filter!(A, B, D, E, F);
+impl_filter!(A, B, D, E, F, G);
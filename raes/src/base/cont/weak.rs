@@ -0,0 +1,84 @@
+use super::*;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// A reference to an entity that may have been despawned, such as a turret's target.
+///
+/// Meant to be stored in the rows of other containers.
+/// Resolve it against the target's [`ContEntities`] before every use.
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct WeakEntity<C: Cont> {
+    entity: Option<Entity<C>>,
+}
+
+impl<C: Cont> WeakEntity<C> {
+    pub fn new(entity: Entity<C>) -> Self {
+        Self {
+            entity: Some(entity),
+        }
+    }
+
+    /// A reference to nothing.
+    pub fn none() -> Self {
+        Self { entity: None }
+    }
+
+    /// Returns the entity if it is still alive.
+    pub fn resolve<const MAX_ENTITIES: usize>(
+        &self,
+        entities: &ContEntities<C, MAX_ENTITIES>,
+    ) -> Option<Entity<C>> {
+        self.entity.filter(|entity| entities.has_entity(entity))
+    }
+
+    /// Whether the reference was set, but its entity has since been despawned.
+    pub fn is_dangling<const MAX_ENTITIES: usize>(
+        &self,
+        entities: &ContEntities<C, MAX_ENTITIES>,
+    ) -> bool {
+        self.entity.is_some() && self.resolve(entities).is_none()
+    }
+
+    pub fn set(&mut self, entity: Entity<C>) {
+        self.entity = Some(entity);
+    }
+
+    pub fn clear(&mut self) {
+        self.entity = None;
+    }
+}
+
+impl<C: Cont> Clone for WeakEntity<C> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<C: Cont> Copy for WeakEntity<C> {}
+
+impl<C: Cont> Default for WeakEntity<C> {
+    fn default() -> Self {
+        Self::none()
+    }
+}
+
+impl<C: Cont> fmt::Debug for WeakEntity<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("WeakEntity").field(&self.entity).finish()
+    }
+}
+
+impl<C: Cont> PartialEq for WeakEntity<C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.entity == other.entity
+    }
+}
+
+impl<C: Cont> Eq for WeakEntity<C> {}
+
+impl<C: Cont> From<Entity<C>> for WeakEntity<C> {
+    fn from(entity: Entity<C>) -> Self {
+        Self::new(entity)
+    }
+}
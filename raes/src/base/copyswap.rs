@@ -4,25 +4,84 @@ pub trait Flushable {
     fn flush(&mut self) {}
 }
 
+/// A double buffer for `Copy` edges owned by a single thread.
+///
+/// On flush, the published data is copied into the new write buffer.
+///
+/// [`CopySwap::get`] does not hold a lock, so it must not race with [`CopySwap::flush`].
+/// Use [`CloneSwap`] for data that owns heap allocations, and
+/// [`TripleBuffer`](crate::base::TripleBuffer) for edges shared between threads.
 #[derive(Default)]
-pub struct CopySwap<T: Copy + Flushable> {
+pub struct CopySwap<T: Copy + Flushable>(DoubleBuffer<T>);
+
+impl<T: Copy + Flushable> CopySwap<T> {
+    pub fn new(init_val: T) -> Self {
+        Self(DoubleBuffer::new(init_val))
+    }
+
+    pub fn flush(&mut self) {
+        self.0.flush()
+    }
+
+    pub fn get(&self) -> &T {
+        self.0.get()
+    }
+
+    pub fn get_mut(&self) -> MutexGuard<'_, T> {
+        self.0.get_mut()
+    }
+}
+
+/// A double buffer for `Clone` edges owned by a single thread, such as those holding `String`s
+/// or `Vec`s.
+///
+/// On flush, the new write buffer is overwritten with [`Clone::clone_from`], which reuses its
+/// allocations instead of freeing and allocating them again every frame. Derived `Clone`
+/// implementations do not forward `clone_from` to their fields, so implement it by hand for
+/// structs that own allocations.
+///
+/// [`CloneSwap::get`] does not hold a lock, so it must not race with [`CloneSwap::flush`].
+#[derive(Default)]
+pub struct CloneSwap<T: Clone + Flushable>(DoubleBuffer<T>);
+
+impl<T: Clone + Flushable> CloneSwap<T> {
+    pub fn new(init_val: T) -> Self {
+        Self(DoubleBuffer::new(init_val))
+    }
+
+    pub fn flush(&mut self) {
+        self.0.flush()
+    }
+
+    pub fn get(&self) -> &T {
+        self.0.get()
+    }
+
+    pub fn get_mut(&self) -> MutexGuard<'_, T> {
+        self.0.get_mut()
+    }
+}
+
+/// The buffers of [`CopySwap`] and [`CloneSwap`]. `clone_from` is a plain copy for `Copy` data.
+#[derive(Default)]
+struct DoubleBuffer<T> {
     datas: [Mutex<T>; 2],
     read_index: usize,
 }
 
-impl<T: Copy + Flushable> CopySwap<T> {
-    pub fn new(init_val: T) -> Self {
+impl<T: Clone + Flushable> DoubleBuffer<T> {
+    fn new(init_val: T) -> Self {
         Self {
-            datas: [Mutex::new(init_val), Mutex::new(init_val)],
+            datas: [Mutex::new(init_val.clone()), Mutex::new(init_val)],
             read_index: 0,
         }
     }
 
-    pub fn flush(&mut self) {
+    fn flush(&mut self) {
         let write_index = self.get_write_index();
         let mut write = self.datas[write_index].lock();
         write.flush();
-        *self.datas[self.read_index].lock() = *write;
+        self.datas[self.read_index].lock().clone_from(&write);
         self.read_index = write_index;
     }
 
@@ -30,11 +89,11 @@ impl<T: Copy + Flushable> CopySwap<T> {
         (self.read_index + 1) % 2
     }
 
-    pub fn get(&self) -> &T {
+    fn get(&self) -> &T {
         unsafe { &*self.datas[self.read_index].data_ptr() }
     }
 
-    pub fn get_mut(&self) -> MutexGuard<T> {
+    fn get_mut(&self) -> MutexGuard<'_, T> {
         self.datas[self.get_write_index()].lock()
     }
 }
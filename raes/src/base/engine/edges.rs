@@ -0,0 +1,118 @@
+use super::*;
+use crate::base::{Arc, Flushable, Mutex, RwLock, TripleBuffer};
+use std::any::{type_name, Any, TypeId};
+
+/// Data shared between a producer and its subscribers through [`Edges`].
+pub trait Edge: Send + Sync + 'static {
+    /// Called by [`Edges::end_frame`].
+    fn end_frame(&self) {}
+}
+
+/// Published at frame boundaries, unless the writer is still writing or the publish is deferred,
+/// see [`WriteGuard::publish`](crate::base::WriteGuard::publish).
+impl<T> Edge for TripleBuffer<T>
+where
+    T: Clone + Flushable + Send + Sync + 'static,
+{
+    fn end_frame(&self) {
+        if let Some(mut write) = self.try_write() {
+            write.publish();
+        }
+    }
+}
+
+/// Flushed at frame boundaries.
+impl<T: Flushable + Send + Sync + 'static> Edge for RwLock<T> {
+    fn end_frame(&self) {
+        self.write().flush();
+    }
+}
+
+/// Flushed at frame boundaries.
+impl<T: Flushable + Send + 'static> Edge for Mutex<T> {
+    fn end_frame(&self) {
+        self.lock().flush();
+    }
+}
+
+#[derive(Clone)]
+struct EdgeEntry {
+    /// `None` for edges whose producer ends their frames itself.
+    edge: Option<Arc<dyn Edge>>,
+    any: Arc<dyn Any + Send + Sync>,
+}
+
+/// Edges published by type, so that scenes and systems can subscribe to them without being
+/// wired to their producers by hand.
+///
+/// Clones share the edges published so far.
+#[derive(Clone, Default)]
+pub struct Edges {
+    /// In publish order, which is the order edges end their frames in.
+    entries: Vec<EdgeEntry>,
+    indices: HashMap<TypeId, usize>,
+}
+
+impl Edges {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Make `edge` available to subscribers of `E`.
+    /// Only one producer may publish each edge type.
+    pub fn publish<E: Edge>(&mut self, edge: E) -> Result<Arc<E>, EngineError> {
+        let edge = Arc::new(edge);
+        self.publish_arc(Arc::clone(&edge))?;
+        Ok(edge)
+    }
+
+    pub fn publish_arc<E: Edge>(&mut self, edge: Arc<E>) -> Result<(), EngineError> {
+        self.insert(Some(Arc::clone(&edge) as Arc<dyn Edge>), edge)
+    }
+
+    /// Like [`Edges::publish`], but [`Edges::end_frame`] skips the edge, because its producer
+    /// publishes or flushes it on its own frames, like [`SurfaceCont`](crate::surface::SurfaceCont).
+    pub fn publish_unmanaged<E: Edge>(&mut self, edge: E) -> Result<Arc<E>, EngineError> {
+        let edge = Arc::new(edge);
+        self.insert(None, Arc::clone(&edge))?;
+        Ok(edge)
+    }
+
+    fn insert<E: Edge>(
+        &mut self,
+        edge: Option<Arc<dyn Edge>>,
+        any: Arc<E>,
+    ) -> Result<(), EngineError> {
+        let id = TypeId::of::<E>();
+        if self.indices.contains_key(&id) {
+            Err(EngineError::EdgeAlreadyPublished(String::from(
+                type_name::<E>(),
+            )))?
+        }
+
+        self.indices.insert(id, self.entries.len());
+        self.entries.push(EdgeEntry { edge, any });
+        Ok(())
+    }
+
+    pub fn subscribe<E: Edge>(&self) -> Result<Arc<E>, EngineError> {
+        let index = self
+            .indices
+            .get(&TypeId::of::<E>())
+            .ok_or_else(|| EngineError::EdgeMissingProducer(String::from(type_name::<E>())))?;
+        Ok(Arc::clone(&self.entries[*index].any).downcast().unwrap())
+    }
+
+    pub fn has<E: Edge>(&self) -> bool {
+        self.indices.contains_key(&TypeId::of::<E>())
+    }
+
+    /// Call once per frame, after producers have written, to publish or flush every edge
+    /// published with [`Edges::publish`] or [`Edges::publish_arc`].
+    /// Running scenes do so through [`Frames::end_frame`](super::Frames::end_frame).
+    pub fn end_frame(&self) {
+        for edge in self.entries.iter().filter_map(|entry| entry.edge.as_ref()) {
+            edge.end_frame();
+        }
+    }
+}
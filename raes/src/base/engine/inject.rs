@@ -0,0 +1,183 @@
+use super::{Edge, Edges, IceBox, Preservable};
+use crate::base::{Arc, Manual, ManualError};
+use std::{
+    any::{type_name, TypeId},
+    fmt,
+};
+use thiserror::Error;
+
+pub use raes_derive::Inject;
+
+/// Fills a scene's fields from the [`IceBox`] and [`Edges`] before [`Scene::run`](super::Scene::run).
+///
+/// Derive it and mark `Manual` fields with `#[inject(icebox)]` to take a `Box<P>` out of the
+/// icebox, or with `#[inject(edge)]` to subscribe to an `Arc<E>` edge. Every dependency is
+/// checked before any is taken, so a failed injection leaves the icebox untouched. The icebox
+/// holds one value of each type, so only one field may take it.
+///
+/// [`Engine::add_scene`](super::Engine::add_scene) requires it. Scenes with nothing to inject
+/// derive it without marking any field.
+///
+/// ```ignore
+/// #[derive(Default, Serialize, Deserialize, Inject)]
+/// struct HelloScene {
+///     #[serde(skip)]
+///     #[inject(icebox)]
+///     surface: Manual<Box<SurfaceCont>>,
+///     #[serde(skip)]
+///     #[inject(edge)]
+///     surface_edge: Manual<Arc<TripleBuffer<SurfaceEdgeData>>>,
+/// }
+/// ```
+pub trait Inject {
+    fn inject(&mut self, icebox: &mut IceBox, edges: &Edges) -> Result<(), InjectError>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DependencySource {
+    IceBox,
+    Edge,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissingDependency {
+    pub field: &'static str,
+    pub source: DependencySource,
+    pub type_name: &'static str,
+}
+
+impl fmt::Display for MissingDependency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.source {
+            DependencySource::IceBox => {
+                write!(
+                    f,
+                    "`{}` needs `{}` in the icebox",
+                    self.field, self.type_name
+                )
+            }
+            DependencySource::Edge => {
+                write!(
+                    f,
+                    "`{}` needs a producer of edge `{}`",
+                    self.field, self.type_name
+                )
+            }
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum InjectError {
+    #[error("While injecting `{scene}`: {}.", list_missing(.missing))]
+    Missing {
+        scene: &'static str,
+        missing: Vec<MissingDependency>,
+    },
+    #[error("While injecting `{scene}`: {} were already injected.", list_fields(.fields))]
+    AlreadyInjected {
+        scene: &'static str,
+        fields: Vec<&'static str>,
+    },
+    #[error("While injecting: {0}")]
+    Manual(#[from] ManualError),
+}
+
+fn list_missing(missing: &[MissingDependency]) -> String {
+    missing
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn list_fields(fields: &[&'static str]) -> String {
+    fields
+        .iter()
+        .map(|field| format!("`{}`", field))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Problems found by the `check_` functions, before anything is injected.
+#[doc(hidden)]
+#[derive(Default)]
+pub struct InjectCheck {
+    missing: Vec<MissingDependency>,
+    injected: Vec<&'static str>,
+    taken: Vec<TypeId>,
+}
+
+impl InjectCheck {
+    pub fn finish(self, scene: &'static str) -> Result<(), InjectError> {
+        if !self.injected.is_empty() {
+            Err(InjectError::AlreadyInjected {
+                scene,
+                fields: self.injected,
+            })?
+        }
+        if !self.missing.is_empty() {
+            Err(InjectError::Missing {
+                scene,
+                missing: self.missing,
+            })?
+        }
+        Ok(())
+    }
+}
+
+#[doc(hidden)]
+pub fn check_icebox<P: Preservable + 'static>(
+    field: &Manual<Box<P>>,
+    icebox: &IceBox,
+    name: &'static str,
+    check: &mut InjectCheck,
+) {
+    if field.is_initialized() {
+        check.injected.push(name);
+    }
+    //  A second field of the same type would find the icebox already emptied by the first.
+    let id = TypeId::of::<P>();
+    if !icebox.contains::<P>() || check.taken.contains(&id) {
+        check.missing.push(MissingDependency {
+            field: name,
+            source: DependencySource::IceBox,
+            type_name: type_name::<P>(),
+        });
+    }
+    check.taken.push(id);
+}
+
+#[doc(hidden)]
+pub fn inject_icebox<P: Preservable + 'static>(
+    field: &mut Manual<Box<P>>,
+    icebox: &mut IceBox,
+) -> Result<(), InjectError> {
+    //  Checked by `check_icebox`, so the field is empty and the value is there.
+    Ok(field.try_init(icebox.take().unwrap())?)
+}
+
+#[doc(hidden)]
+pub fn check_edge<E: Edge>(
+    field: &Manual<Arc<E>>,
+    edges: &Edges,
+    name: &'static str,
+    check: &mut InjectCheck,
+) {
+    if field.is_initialized() {
+        check.injected.push(name);
+    }
+    if !edges.has::<E>() {
+        check.missing.push(MissingDependency {
+            field: name,
+            source: DependencySource::Edge,
+            type_name: type_name::<E>(),
+        });
+    }
+}
+
+#[doc(hidden)]
+pub fn inject_edge<E: Edge>(field: &mut Manual<Arc<E>>, edges: &Edges) -> Result<(), InjectError> {
+    //  Checked by `check_edge`.
+    Ok(field.try_init(edges.subscribe().unwrap())?)
+}
@@ -1,8 +1,11 @@
+use crate::base::EventBufferConfig;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::collections::HashMap;
 use thiserror::Error;
 
 mod args;
+mod edges;
+mod inject;
 mod scene;
 
 /// Implementations for platforms that have a filesystem.
@@ -14,7 +17,11 @@ use fs_platform::{
     fs_platform_write_scene,
 };
 
-pub use scene::{IceBox, Preservable, Scene, SceneExit};
+pub use edges::{Edge, Edges};
+#[doc(hidden)]
+pub use inject::{check_edge, check_icebox, inject_edge, inject_icebox, InjectCheck};
+pub use inject::{DependencySource, Inject, InjectError, MissingDependency};
+pub use scene::{Frames, IceBox, Preservable, Scene, SceneExit};
 
 const MOUNT_ROOT_CONFIG_FILE_NAME: &str = "raes.ron";
 
@@ -44,6 +51,10 @@ pub enum EngineError {
     ParseConfig(String),
     #[error("Loaded scene has not yet been added.")]
     SceneNotAdded(String),
+    #[error("Edge `{0}` was requested, but no producer published it.")]
+    EdgeMissingProducer(String),
+    #[error("Edge `{0}` was published more than once.")]
+    EdgeAlreadyPublished(String),
 }
 
 #[derive(Default)]
@@ -56,10 +67,18 @@ struct EngineArgs {
 #[derive(Debug, Deserialize)]
 struct EngineConfig {
     load_scene: String,
+    /// Keyed by buffer name, such as `"surface.key_events"`.
+    #[serde(default)]
+    event_buffers: HashMap<String, EventBufferConfig>,
 }
 
-type SceneLoader = fn(&str) -> Result<Box<dyn Scene>, EngineError>;
-type SceneDefaultWrite = fn(&str) -> Result<Box<dyn Scene>, EngineError>;
+/// A scene with its dependencies, so that the engine can inject it.
+trait InjectScene: Scene + Inject {}
+
+impl<S: Scene + Inject> InjectScene for S {}
+
+type SceneLoader = fn(&str) -> Result<Box<dyn InjectScene>, EngineError>;
+type SceneDefaultWrite = fn(&str) -> Result<Box<dyn InjectScene>, EngineError>;
 
 struct SceneData {
     loader: SceneLoader,
@@ -69,6 +88,7 @@ struct SceneData {
 pub struct Engine {
     config: EngineConfig,
     scenes: HashMap<String, SceneData>,
+    edges: Edges,
 }
 
 impl Engine {
@@ -76,19 +96,31 @@ impl Engine {
         let args = fs_platform_get_args()?;
         let config_str = fs_platform_get_config_str(&args)?;
 
-        let mut config: EngineConfig =
-            ron::from_str(&config_str).map_err(|e| EngineError::ParseConfig(format!("{}", e)))?;
-
+        let mut engine = Self::from_config_str(&config_str)?;
         if let Some(scene) = args.scene {
-            config.load_scene = scene;
+            engine.config.load_scene = scene;
         }
 
+        Ok(engine)
+    }
+
+    /// Like [`Engine::ignite`], but configured by the contents of a `raes.ron` without reading
+    /// arguments or searching for a mount.
+    pub fn from_config_str(config_str: &str) -> Result<Self, EngineError> {
+        let config: EngineConfig =
+            ron::from_str(config_str).map_err(|e| EngineError::ParseConfig(format!("{}", e)))?;
+
         let scenes = HashMap::new();
+        let edges = Edges::new();
 
-        Ok(Engine { config, scenes })
+        Ok(Engine {
+            config,
+            scenes,
+            edges,
+        })
     }
 
-    pub fn add_scene<S: Scene + Serialize + DeserializeOwned + Default + 'static>(
+    pub fn add_scene<S: Scene + Inject + Serialize + DeserializeOwned + Default + 'static>(
         &mut self,
         scene_names: &[&str],
     ) -> &mut Self {
@@ -119,6 +151,20 @@ impl Engine {
         self
     }
 
+    pub fn edges(&self) -> &Edges {
+        &self.edges
+    }
+
+    /// Producers publish their edges here before scenes are run.
+    pub fn edges_mut(&mut self) -> &mut Edges {
+        &mut self.edges
+    }
+
+    /// The `event_buffers` entry named `name` in the engine config, if set.
+    pub fn event_buffer_config(&self, name: &str) -> Option<EventBufferConfig> {
+        self.config.event_buffers.get(name).copied()
+    }
+
     pub fn get_first_scene(&self) -> String {
         self.config.load_scene.clone()
     }
@@ -126,7 +172,7 @@ impl Engine {
     pub fn run_scene(
         &mut self,
         scene: &str,
-        icebox: IceBox,
+        mut icebox: IceBox,
     ) -> anyhow::Result<Option<(String, IceBox)>> {
         let scene_data = self
             .scenes
@@ -143,7 +189,9 @@ impl Engine {
                 }
             }
         };
-        let res = match scene.run(icebox)? {
+        scene.inject(&mut icebox, &self.edges)?;
+        scene.connect(&self.edges)?;
+        let res = match scene.run(icebox, Frames::new(self.edges.clone()))? {
             SceneExit::End => None,
             SceneExit::Next(next, icebox) => Some((next, icebox)),
         };
@@ -1,3 +1,4 @@
+use super::Edges;
 use std::{
     any::{type_name, TypeId},
     collections::HashMap,
@@ -8,8 +9,42 @@ pub enum SceneExit {
     End,
 }
 
+/// Fields marked by [`Inject`](super::Inject) are filled before [`Scene::connect`].
 pub trait Scene {
-    fn run(&mut self, icebox: IceBox) -> anyhow::Result<SceneExit>;
+    /// Subscribe to edges before [`Scene::run`].
+    fn connect(&mut self, _edges: &Edges) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    /// Call [`Frames::end_frame`] at the end of every frame of the scene's loop.
+    fn run(&mut self, icebox: IceBox, frames: Frames) -> anyhow::Result<SceneExit>;
+}
+
+/// The frame boundaries of a running [`Scene`], handed to [`Scene::run`] by the engine.
+///
+/// Ending a frame publishes or flushes every edge of the engine, except those whose producers
+/// end their own frames, such as the surface edge.
+pub struct Frames {
+    edges: Edges,
+    frame: u64,
+}
+
+impl Frames {
+    /// Ends frames for `edges` and every clone of it.
+    pub fn new(edges: Edges) -> Self {
+        Self { edges, frame: 0 }
+    }
+
+    /// Call once per frame, after producers have written. See [`Edges::end_frame`].
+    pub fn end_frame(&mut self) {
+        self.edges.end_frame();
+        self.frame += 1;
+    }
+
+    /// The number of frames ended so far.
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
 }
 
 pub trait Preservable {}
@@ -30,6 +65,10 @@ impl IceBox {
         Some(unsafe { Box::from_raw(Box::leak(p) as *mut dyn Preservable as *mut P) })
     }
 
+    pub fn contains<P: Preservable + 'static>(&self) -> bool {
+        self.preserved.contains_key(&TypeId::of::<P>())
+    }
+
     pub fn put<P: Preservable + 'static>(&mut self, data: Box<P>) {
         let id = TypeId::of::<P>();
         if self.preserved.insert(id, data).is_some() {
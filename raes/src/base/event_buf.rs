@@ -1,75 +1,152 @@
-#[derive(Debug, Clone, Copy)]
-pub struct EventBuffer<T, const MAX: usize> {
-    events: [T; MAX],
-    events_count: usize,
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// What an [`EventBuffer`] does with events pushed once it is full.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Overflow {
+    /// Keep the oldest events and drop the pushed one.
+    #[default]
+    DropNewest,
+    /// Drop the oldest event to make room, like a ring buffer.
+    DropOldest,
+    /// Keep every event, allocating past the capacity.
+    Spill,
+}
+
+/// Set per buffer in the engine config, see [`Engine::event_buffer_config`](crate::base::Engine::event_buffer_config).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventBufferConfig {
+    pub capacity: usize,
+    #[serde(default)]
+    pub overflow: Overflow,
+}
+
+/// Events collected during one frame, cleared on flush.
+///
+/// Room for `capacity` events is allocated upfront.
+/// Events that do not fit are handled by the buffer's [`Overflow`] policy and counted.
+#[derive(Debug)]
+pub struct EventBuffer<T> {
+    events: VecDeque<T>,
+    capacity: usize,
+    overflow: Overflow,
+    dropped: usize,
+    total_dropped: u64,
 }
 
-impl<T, const MAX: usize> EventBuffer<T, MAX> {
+impl<T> EventBuffer<T> {
+    pub fn new(capacity: usize, overflow: Overflow) -> Self {
+        Self {
+            events: VecDeque::with_capacity(capacity),
+            capacity,
+            overflow,
+            dropped: 0,
+            total_dropped: 0,
+        }
+    }
+
+    pub fn from_config(config: EventBufferConfig) -> Self {
+        Self::new(config.capacity, config.overflow)
+    }
+
     pub fn push(&mut self, event: T) {
-        let idx = self.events_count + 1;
-        if idx < MAX {
-            self.events[idx] = event;
+        if self.events.len() < self.capacity {
+            self.events.push_back(event);
+            return;
+        }
+
+        match self.overflow {
+            Overflow::DropNewest => self.drop_event(),
+            Overflow::DropOldest => {
+                if self.events.pop_front().is_some() {
+                    self.events.push_back(event);
+                }
+                self.drop_event();
+            }
+            Overflow::Spill => self.events.push_back(event),
         }
     }
 
+    fn drop_event(&mut self) {
+        #[cfg(debug_assertions)]
+        if self.dropped == 0 {
+            eprintln!(
+                "EventBuffer<{}> exceeded its capacity of {} and is dropping events ({:?}).",
+                std::any::type_name::<T>(),
+                self.capacity,
+                self.overflow
+            );
+        }
+
+        self.dropped += 1;
+        self.total_dropped += 1;
+    }
+
     pub fn flush(&mut self) {
-        self.events_count = 0;
+        self.events.clear();
+        self.dropped = 0;
     }
 
-    pub fn as_slice(&self) -> &[T] {
-        &self.events[0..self.events_count]
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &T> {
+        self.events.iter()
     }
 
-    pub fn iter(&self) -> &[T] {
-        self.as_slice()
+    /// The events in order, split in two where the ring wraps around.
+    pub fn as_slices(&self) -> (&[T], &[T]) {
+        self.events.as_slices()
     }
-}
 
-impl<T, const MAX: usize> EventBuffer<T, MAX>
-where
-    T: Clone,
-{
-    /// `init_val` will never be read.
-    /// Rather, the value is just used to safely initialize the buffer.
-    pub fn new(init_val: T) -> Self {
-        let events = unsafe {
-            (0..MAX)
-                .map(|_| init_val.clone())
-                .collect::<Vec<_>>()
-                .try_into()
-                .unwrap_unchecked()
-        };
-        Self {
-            events,
-            events_count: 0,
-        }
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn overflow(&self) -> Overflow {
+        self.overflow
+    }
+
+    /// Events dropped since the last flush.
+    pub fn dropped(&self) -> usize {
+        self.dropped
+    }
+
+    /// Events dropped since the buffer was created.
+    pub fn total_dropped(&self) -> u64 {
+        self.total_dropped
+    }
+
+    /// Events stored past the capacity with [`Overflow::Spill`].
+    pub fn spilled(&self) -> usize {
+        self.events.len().saturating_sub(self.capacity)
     }
 }
 
-impl<T, const MAX: usize> EventBuffer<T, MAX>
-where
-    T: Default,
-{
-    pub fn new_with_default() -> Self {
-        let events = unsafe {
-            (0..MAX)
-                .map(|_| T::default())
-                .collect::<Vec<_>>()
-                .try_into()
-                .unwrap_unchecked()
-        };
+/// Reuses the allocation on `clone_from`, so edges can copy buffers every frame.
+impl<T: Clone> Clone for EventBuffer<T> {
+    fn clone(&self) -> Self {
+        let mut events = VecDeque::with_capacity(self.capacity.max(self.events.len()));
+        events.extend(self.events.iter().cloned());
         Self {
             events,
-            events_count: 0,
+            capacity: self.capacity,
+            overflow: self.overflow,
+            dropped: self.dropped,
+            total_dropped: self.total_dropped,
         }
     }
-}
 
-impl<T, const MAX: usize> Default for EventBuffer<T, MAX>
-where
-    T: Default,
-{
-    fn default() -> Self {
-        Self::new_with_default()
+    fn clone_from(&mut self, source: &Self) {
+        self.events.clone_from(&source.events);
+        self.capacity = source.capacity;
+        self.overflow = source.overflow;
+        self.dropped = source.dropped;
+        self.total_dropped = source.total_dropped;
     }
 }
@@ -0,0 +1,229 @@
+use crate::base::{Edge, Flushable};
+use cgmath::{
+    num_traits::NumCast, BaseFloat, Decomposed, EuclideanSpace, Point2, Point3, Quaternion,
+    Vector2, Vector3, Vector4, VectorSpace,
+};
+use parking_lot::{Mutex, MutexGuard, RwLock, RwLockReadGuard};
+use std::{collections::VecDeque, time::Instant};
+
+/// Values that can be blended between two states, such as positions between simulation frames.
+pub trait Interpolate {
+    /// `alpha` of 0 gives `self` and 1 gives `to`.
+    fn interpolate(&self, to: &Self, alpha: f32) -> Self;
+}
+
+impl Interpolate for f32 {
+    fn interpolate(&self, to: &Self, alpha: f32) -> Self {
+        self + (to - self) * alpha
+    }
+}
+
+impl Interpolate for f64 {
+    fn interpolate(&self, to: &Self, alpha: f32) -> Self {
+        self + (to - self) * alpha as f64
+    }
+}
+
+macro_rules! impl_interpolate_lerp {
+    ($($ty:ident),*) => {
+        $(
+            impl<S: BaseFloat> Interpolate for $ty<S> {
+                fn interpolate(&self, to: &Self, alpha: f32) -> Self {
+                    self.lerp(*to, scalar(alpha))
+                }
+            }
+        )*
+    };
+}
+
+impl_interpolate_lerp!(Vector2, Vector3, Vector4);
+
+impl<S: BaseFloat> Interpolate for Point2<S> {
+    fn interpolate(&self, to: &Self, alpha: f32) -> Self {
+        Point2::from_vec(self.to_vec().lerp(to.to_vec(), scalar(alpha)))
+    }
+}
+
+impl<S: BaseFloat> Interpolate for Point3<S> {
+    fn interpolate(&self, to: &Self, alpha: f32) -> Self {
+        Point3::from_vec(self.to_vec().lerp(to.to_vec(), scalar(alpha)))
+    }
+}
+
+impl<S: BaseFloat> Interpolate for Quaternion<S> {
+    fn interpolate(&self, to: &Self, alpha: f32) -> Self {
+        self.slerp(*to, scalar(alpha))
+    }
+}
+
+impl<S: BaseFloat> Interpolate for Decomposed<Vector3<S>, Quaternion<S>> {
+    fn interpolate(&self, to: &Self, alpha: f32) -> Self {
+        Decomposed {
+            scale: self.scale + (to.scale - self.scale) * scalar(alpha),
+            rot: self.rot.interpolate(&to.rot, alpha),
+            disp: self.disp.interpolate(&to.disp, alpha),
+        }
+    }
+}
+
+fn scalar<S: BaseFloat>(alpha: f32) -> S {
+    <S as NumCast>::from(alpha).unwrap()
+}
+
+/// One published frame of an [`EdgeHistory`].
+#[derive(Debug, Clone)]
+pub struct Published<T> {
+    /// Counts up from 0 with every publish.
+    pub frame: u64,
+    pub time: Instant,
+    pub data: T,
+}
+
+/// An edge that keeps the last `capacity` published frames, so that readers can interpolate
+/// between simulation states or compare this frame with the previous one.
+///
+/// The writer continues from a flushed copy of every published frame, like
+/// [`TripleBuffer`](crate::base::TripleBuffer). Once full, publishing overwrites the oldest frame
+/// in place.
+pub struct EdgeHistory<T> {
+    write: Mutex<T>,
+    frames: RwLock<VecDeque<Published<T>>>,
+    capacity: usize,
+}
+
+impl<T: Clone + Flushable> EdgeHistory<T> {
+    /// `capacity` is clamped to at least 1.
+    pub fn new(init_val: T, capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            write: Mutex::new(init_val),
+            frames: RwLock::new(VecDeque::with_capacity(capacity)),
+            capacity,
+        }
+    }
+
+    pub fn write(&self) -> MutexGuard<'_, T> {
+        self.write.lock()
+    }
+
+    /// Returns the number of the published frame.
+    pub fn publish(&self) -> u64 {
+        self.publish_from(&mut self.write.lock())
+    }
+
+    fn publish_from(&self, write: &mut T) -> u64 {
+        let mut frames = self.frames.write();
+        let frame = frames.back().map(|latest| latest.frame + 1).unwrap_or(0);
+        let time = Instant::now();
+        if frames.len() == self.capacity {
+            let mut oldest = frames.pop_front().unwrap();
+            oldest.frame = frame;
+            oldest.time = time;
+            oldest.data.clone_from(write);
+            frames.push_back(oldest);
+        } else {
+            frames.push_back(Published {
+                frame,
+                time,
+                data: write.clone(),
+            });
+        }
+        drop(frames);
+
+        write.flush();
+        frame
+    }
+
+    /// Lock the published frames for reading. The writer can keep writing, but not publish,
+    /// until the guard is dropped.
+    pub fn read(&self) -> HistoryGuard<'_, T> {
+        HistoryGuard {
+            frames: self.frames.read(),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+}
+
+/// Published at frame boundaries, unless the writer is still writing.
+impl<T> Edge for EdgeHistory<T>
+where
+    T: Clone + Flushable + Send + Sync + 'static,
+{
+    fn end_frame(&self) {
+        if let Some(mut write) = self.write.try_lock() {
+            self.publish_from(&mut write);
+        }
+    }
+}
+
+/// The published frames of an [`EdgeHistory`], oldest first.
+pub struct HistoryGuard<'a, T> {
+    frames: RwLockReadGuard<'a, VecDeque<Published<T>>>,
+}
+
+impl<'a, T> HistoryGuard<'a, T> {
+    pub fn latest(&self) -> Option<&Published<T>> {
+        self.frames.back()
+    }
+
+    /// The frame published before [`HistoryGuard::latest`].
+    pub fn previous(&self) -> Option<&Published<T>> {
+        self.frames.iter().nth_back(1)
+    }
+
+    pub fn get(&self, frame: u64) -> Option<&Published<T>> {
+        let oldest = self.frames.front()?.frame;
+        self.frames.get(frame.checked_sub(oldest)? as usize)
+    }
+
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &Published<T>> {
+        self.frames.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Blend the value selected by `select` from [`HistoryGuard::previous`] towards
+    /// [`HistoryGuard::latest`]. With a single frame, its value is returned as is.
+    pub fn interpolate<V: Interpolate>(&self, alpha: f32, select: impl Fn(&T) -> V) -> Option<V> {
+        let latest = select(&self.latest()?.data);
+        Some(match self.previous() {
+            Some(previous) => select(&previous.data).interpolate(&latest, alpha),
+            None => latest,
+        })
+    }
+
+    /// Blend the value selected by `select` between the two frames published around `time`.
+    /// Times outside of the history are clamped to the oldest or latest frame.
+    pub fn interpolate_at<V: Interpolate>(
+        &self,
+        time: Instant,
+        select: impl Fn(&T) -> V,
+    ) -> Option<V> {
+        let after = self
+            .frames
+            .iter()
+            .position(|published| published.time >= time);
+        let (from, to) = match after {
+            Some(0) => return Some(select(&self.frames.front()?.data)),
+            Some(after) => (&self.frames[after - 1], &self.frames[after]),
+            None => return Some(select(&self.frames.back()?.data)),
+        };
+
+        let span = to.time.duration_since(from.time).as_secs_f32();
+        let alpha = if span > 0.0 {
+            time.duration_since(from.time).as_secs_f32() / span
+        } else {
+            1.0
+        };
+        Some(select(&from.data).interpolate(&select(&to.data), alpha))
+    }
+}
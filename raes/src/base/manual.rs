@@ -1,4 +1,13 @@
 use std::ops::{Deref, DerefMut};
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ManualError {
+    #[error("Manual<{0}> initialized twice.")]
+    AlreadyInitialized(&'static str),
+    #[error("Manual<{0}> is not initialized.")]
+    NotInitialized(&'static str),
+}
 
 pub struct Manual<T> {
     data: Option<T>,
@@ -12,19 +21,44 @@ impl<T> Default for Manual<T> {
 
 impl<T> Manual<T> {
     pub fn init(&mut self, data: T) {
+        self.try_init(data).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Like [`Manual::init`], but keeps the current data instead of panicking.
+    pub fn try_init(&mut self, data: T) -> Result<(), ManualError> {
         if self.data.is_some() {
-            panic!("Manual<{}> initialized twice.", std::any::type_name::<T>())
+            Err(ManualError::AlreadyInitialized(std::any::type_name::<T>()))?
         }
 
         self.data = Some(data);
+        Ok(())
     }
 
     pub fn take(&mut self) -> T {
-        if self.data.is_none() {
-            panic!("Manual<{}> nothing to take.", std::any::type_name::<T>())
-        }
+        self.try_take()
+            .unwrap_or_else(|_| panic!("Manual<{}> nothing to take.", std::any::type_name::<T>()))
+    }
+
+    pub fn try_take(&mut self) -> Result<T, ManualError> {
+        self.data
+            .take()
+            .ok_or(ManualError::NotInitialized(std::any::type_name::<T>()))
+    }
 
-        std::mem::replace(&mut self.data, None).unwrap()
+    pub fn try_get(&self) -> Result<&T, ManualError> {
+        self.data
+            .as_ref()
+            .ok_or(ManualError::NotInitialized(std::any::type_name::<T>()))
+    }
+
+    pub fn try_get_mut(&mut self) -> Result<&mut T, ManualError> {
+        self.data
+            .as_mut()
+            .ok_or(ManualError::NotInitialized(std::any::type_name::<T>()))
+    }
+
+    pub fn is_initialized(&self) -> bool {
+        self.data.is_some()
     }
 }
 
@@ -32,26 +66,22 @@ impl<T> Deref for Manual<T> {
     type Target = T;
 
     fn deref(&self) -> &Self::Target {
-        if self.data.is_none() {
+        self.try_get().unwrap_or_else(|_| {
             panic!(
                 "&Manual<{}> is not initialized.",
                 std::any::type_name::<T>()
             )
-        }
-
-        self.data.as_ref().unwrap()
+        })
     }
 }
 
 impl<T> DerefMut for Manual<T> {
     fn deref_mut(&mut self) -> &mut Self::Target {
-        if self.data.is_none() {
+        self.try_get_mut().unwrap_or_else(|_| {
             panic!(
                 "&mut Manual<{}> is not initialized.",
                 std::any::type_name::<T>()
             )
-        }
-
-        self.data.as_mut().unwrap()
+        })
     }
 }
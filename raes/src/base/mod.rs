@@ -1,14 +1,30 @@
+mod channel;
 mod cont;
 mod copyswap;
 mod engine;
 mod event_buf;
+mod history;
 mod manual;
+mod triple;
 
 pub use anyhow::Error;
-pub use cont::{Cont, ContEntities, Entity, Row};
-pub use copyswap::{CopySwap, Flushable};
-pub use engine::{Engine, EngineError, IceBox, Preservable, Scene, SceneExit};
-pub use event_buf::EventBuffer;
-pub use manual::Manual;
+pub use channel::{EventChannel, EventRead, EventReader};
+pub use cont::{
+    Commands, Compose, Cont, ContEntities, ContError, ContSpawn, Cursor, Delta, EntitiesDelta,
+    EntitiesSnapshot, Entity, Fetch, Filter, Hierarchy, HierarchyError, Mut, Provisional, Query,
+    QueryCursor, QueryIter, Rollback, Row, RowSnapshot, Snapshot, SparseRow, SpatialGrid, Spawned,
+    StableId, StableIds, TagRow, Target, WeakEntity, With, Without, GROWABLE,
+};
+pub use copyswap::{CloneSwap, CopySwap, Flushable};
+#[doc(hidden)]
+pub use engine::{check_edge, check_icebox, inject_edge, inject_icebox, InjectCheck};
+pub use engine::{
+    DependencySource, Edge, Edges, Engine, EngineError, Frames, IceBox, Inject, InjectError,
+    MissingDependency, Preservable, Scene, SceneExit,
+};
+pub use event_buf::{EventBuffer, EventBufferConfig, Overflow};
+pub use history::{EdgeHistory, HistoryGuard, Interpolate, Published};
+pub use manual::{Manual, ManualError};
 pub use parking_lot::*;
 pub use std::sync::Arc;
+pub use triple::{ReadGuard, TripleBuffer, WriteGuard};
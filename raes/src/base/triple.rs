@@ -0,0 +1,169 @@
+use crate::base::Flushable;
+use std::{
+    cell::UnsafeCell,
+    ops::{Deref, DerefMut},
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+};
+
+/// A lock-free edge between one writer and any number of readers, which may live on different
+/// threads and share it through an `Arc`.
+///
+/// The writer fills a private buffer and [`WriteGuard::publish`]es it as the latest buffer.
+/// Readers get a [`ReadGuard`] to whichever buffer was latest when they started reading, so a
+/// guard always sees one consistent publish, and readers that overlap each other still see newer
+/// publishes. The third buffer is kept spare for the writer to continue in.
+///
+/// After publishing, the writer continues from a copy of the published data, and
+/// [`Flushable::flush`] is called on it to reset per-frame state such as events.
+pub struct TripleBuffer<T> {
+    buffers: [UnsafeCell<T>; 3],
+    /// Number of [`ReadGuard`]s of each buffer.
+    readers: [AtomicUsize; 3],
+    /// Only written by the writer.
+    latest: AtomicUsize,
+    /// Only accessed by the holder of `writing`.
+    write_index: UnsafeCell<usize>,
+    /// Only accessed by the holder of `writing`.
+    spare_index: UnsafeCell<usize>,
+    writing: AtomicBool,
+}
+
+//  Buffers are only accessed mutably by the single writer, and never while a reader can see them.
+unsafe impl<T: Send> Send for TripleBuffer<T> {}
+unsafe impl<T: Send + Sync> Sync for TripleBuffer<T> {}
+
+impl<T: Clone + Flushable> TripleBuffer<T> {
+    pub fn new(init_val: T) -> Self {
+        Self {
+            buffers: [
+                UnsafeCell::new(init_val.clone()),
+                UnsafeCell::new(init_val.clone()),
+                UnsafeCell::new(init_val),
+            ],
+            readers: Default::default(),
+            latest: AtomicUsize::new(2),
+            write_index: UnsafeCell::new(0),
+            spare_index: UnsafeCell::new(1),
+            writing: AtomicBool::new(false),
+        }
+    }
+
+    /// Get the writer's buffer.
+    /// Panics if another [`WriteGuard`] is alive, see [`TripleBuffer::try_write`].
+    pub fn write(&self) -> WriteGuard<'_, T> {
+        self.try_write().unwrap_or_else(|| {
+            panic!(
+                "On write: TripleBuffer<{}> is already being written.",
+                std::any::type_name::<T>()
+            )
+        })
+    }
+
+    pub fn try_write(&self) -> Option<WriteGuard<'_, T>> {
+        (!self.writing.swap(true, Ordering::Acquire)).then_some(WriteGuard { buffer: self })
+    }
+
+    /// Publish the writer's buffer, see [`WriteGuard::publish`].
+    /// Panics if a [`WriteGuard`] is alive, use [`WriteGuard::publish`] instead.
+    pub fn publish(&self) -> bool {
+        self.write().publish()
+    }
+
+    /// Get the most recently published data.
+    pub fn read(&self) -> ReadGuard<'_, T> {
+        loop {
+            let latest = self.latest.load(Ordering::SeqCst);
+            self.readers[latest].fetch_add(1, Ordering::SeqCst);
+            //  The writer only takes buffers without readers that are not the latest, so once
+            //  counted, the buffer is safe to read if it is still the latest.
+            if self.latest.load(Ordering::SeqCst) == latest {
+                return ReadGuard {
+                    buffer: self,
+                    index: latest,
+                    data: unsafe { &*self.buffers[latest].get() },
+                };
+            }
+            self.readers[latest].fetch_sub(1, Ordering::Release);
+        }
+    }
+}
+
+impl<T: Clone + Flushable + Default> Default for TripleBuffer<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+/// Shared access to a published buffer of a [`TripleBuffer`].
+pub struct ReadGuard<'a, T> {
+    buffer: &'a TripleBuffer<T>,
+    index: usize,
+    data: &'a T,
+}
+
+impl<'a, T> Deref for ReadGuard<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        self.data
+    }
+}
+
+impl<'a, T> Drop for ReadGuard<'a, T> {
+    fn drop(&mut self) {
+        self.buffer.readers[self.index].fetch_sub(1, Ordering::Release);
+    }
+}
+
+/// Exclusive access to the writer's buffer of a [`TripleBuffer`].
+pub struct WriteGuard<'a, T> {
+    buffer: &'a TripleBuffer<T>,
+}
+
+impl<'a, T: Clone + Flushable> WriteGuard<'a, T> {
+    /// Make the written data visible to readers, then continue writing on a flushed copy of it.
+    ///
+    /// Returns `false` without publishing while readers still hold the spare buffer, which happens
+    /// when a guard is kept across two publishes. The writer then keeps writing the same buffer,
+    /// and a later publish makes everything visible.
+    pub fn publish(&mut self) -> bool {
+        let buffer = self.buffer;
+        unsafe {
+            let spare = *buffer.spare_index.get();
+            //  The spare is not the latest, so readers cannot start holding it from here on.
+            if buffer.readers[spare].load(Ordering::SeqCst) != 0 {
+                return false;
+            }
+
+            let published = *buffer.write_index.get();
+            *buffer.spare_index.get() = buffer.latest.swap(published, Ordering::SeqCst);
+            *buffer.write_index.get() = spare;
+
+            //  Readers may be reading `published` by now, but only ever through shared references.
+            let write = &mut *buffer.buffers[spare].get();
+            write.clone_from(&*buffer.buffers[published].get());
+            write.flush();
+        }
+        true
+    }
+}
+
+impl<'a, T> Deref for WriteGuard<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { &*self.buffer.buffers[*self.buffer.write_index.get()].get() }
+    }
+}
+
+impl<'a, T> DerefMut for WriteGuard<'a, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { &mut *self.buffer.buffers[*self.buffer.write_index.get()].get() }
+    }
+}
+
+impl<'a, T> Drop for WriteGuard<'a, T> {
+    fn drop(&mut self) {
+        self.buffer.writing.store(false, Ordering::Release);
+    }
+}
@@ -10,7 +10,7 @@ pub use event::{
 pub use raw_window_handle::{RawDisplayHandle, RawWindowHandle};
 
 pub trait Surface {
-    fn set_window_edge(&mut self, edge: &Arc<CopySwap<SurfaceEdgeData>>);
+    fn set_window_edge(&mut self, edge: &Arc<TripleBuffer<SurfaceEdgeData>>);
 
     fn get_raw_display(&self) -> RawDisplayHandle;
     fn get_raw_window(&self) -> RawWindowHandle;
@@ -25,6 +25,8 @@ pub struct SurfaceCont {
 }
 
 impl SurfaceCont {
+    /// Opens a window, so there is deliberately no `Default`.
+    #[allow(clippy::new_without_default)]
     pub fn new() -> Self {
         SurfaceCont {
             surface: Box::new(winit_platform::WindowCont::new()),
@@ -40,6 +42,23 @@ impl SurfaceCont {
         }
     }
 
+    /// The edge is written and published during [`SurfaceCont::update`].
+    pub fn set_window_edge(&mut self, edge: &Arc<TripleBuffer<SurfaceEdgeData>>) {
+        self.surface.set_window_edge(edge)
+    }
+
+    /// Publish the `TripleBuffer<SurfaceEdgeData>` edge written by this surface, with event
+    /// buffers configured by the engine, see [`SurfaceEdgeData::from_engine`].
+    /// Every [`SurfaceCont::update`] publishes a frame of it.
+    pub fn publish_edges(&mut self, engine: &mut Engine) -> Result<(), EngineError> {
+        let data = SurfaceEdgeData::from_engine(engine);
+        let edge = engine
+            .edges_mut()
+            .publish_unmanaged(TripleBuffer::new(data))?;
+        self.set_window_edge(&edge);
+        Ok(())
+    }
+
     pub fn get_raw_display(&self) -> RawDisplayHandle {
         self.surface.get_raw_display()
     }
@@ -67,40 +86,112 @@ impl SurfaceCont {
 
 impl Preservable for SurfaceCont {}
 
-const MAX_KEY_EVENT_COUNT: usize = 16;
-const MAX_CLICK_EVENT_COUNT: usize = 4;
-const MAX_RESIZE_EVENT_COUNT: usize = 2;
+const KEY_EVENTS: EventBufferConfig = EventBufferConfig {
+    capacity: 16,
+    overflow: Overflow::DropNewest,
+};
+const CLICK_EVENTS: EventBufferConfig = EventBufferConfig {
+    capacity: 4,
+    overflow: Overflow::DropNewest,
+};
+const RESIZE_EVENTS: EventBufferConfig = EventBufferConfig {
+    capacity: 2,
+    overflow: Overflow::DropOldest,
+};
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug)]
 pub struct SurfaceEdgeData {
     modifers: ModifierState,
     mouse_scroll: MouseScrollState,
     mouse_position: MousePositionState,
-    key_events: EventBuffer<KeyEvent, MAX_KEY_EVENT_COUNT>,
-    click_events: EventBuffer<ClickEvent, MAX_CLICK_EVENT_COUNT>,
-    resize_events: EventBuffer<ResizeEvent, MAX_RESIZE_EVENT_COUNT>,
+    key_events: EventBuffer<KeyEvent>,
+    click_events: EventBuffer<ClickEvent>,
+    resize_events: EventBuffer<ResizeEvent>,
 }
 
 impl SurfaceEdgeData {
     pub fn new() -> Self {
+        Self::with_configs(KEY_EVENTS, CLICK_EVENTS, RESIZE_EVENTS)
+    }
+
+    /// Event buffers are configured by the `"surface.key_events"`, `"surface.click_events"` and
+    /// `"surface.resize_events"` entries of the engine config, falling back to the defaults.
+    pub fn from_engine(engine: &Engine) -> Self {
+        let config = |name, default| engine.event_buffer_config(name).unwrap_or(default);
+        Self::with_configs(
+            config("surface.key_events", KEY_EVENTS),
+            config("surface.click_events", CLICK_EVENTS),
+            config("surface.resize_events", RESIZE_EVENTS),
+        )
+    }
+
+    fn with_configs(
+        key_events: EventBufferConfig,
+        click_events: EventBufferConfig,
+        resize_events: EventBufferConfig,
+    ) -> Self {
         Self {
             modifers: ModifierState::default(),
             mouse_scroll: MouseScrollState::default(),
             mouse_position: MousePositionState::default(),
-            key_events: EventBuffer::new(KeyEvent {
-                press: PressState::Up,
-                keycode: VirtualKeyCode::Q,
-            }),
-            click_events: EventBuffer::new(ClickEvent {
-                press: PressState::Up,
-                button: MouseButton::Left,
-            }),
-            resize_events: EventBuffer::new(ResizeEvent {
-                width: 0,
-                height: 0,
-            }),
+            key_events: EventBuffer::from_config(key_events),
+            click_events: EventBuffer::from_config(click_events),
+            resize_events: EventBuffer::from_config(resize_events),
         }
     }
+
+    pub fn modifiers(&self) -> ModifierState {
+        self.modifers
+    }
+
+    pub fn mouse_scroll(&self) -> MouseScrollState {
+        self.mouse_scroll
+    }
+
+    pub fn mouse_position(&self) -> MousePositionState {
+        self.mouse_position
+    }
+
+    pub fn key_events(&self) -> &EventBuffer<KeyEvent> {
+        &self.key_events
+    }
+
+    pub fn click_events(&self) -> &EventBuffer<ClickEvent> {
+        &self.click_events
+    }
+
+    pub fn resize_events(&self) -> &EventBuffer<ResizeEvent> {
+        &self.resize_events
+    }
+}
+
+/// Reuses the event buffers' allocations on `clone_from`.
+impl Clone for SurfaceEdgeData {
+    fn clone(&self) -> Self {
+        Self {
+            modifers: self.modifers,
+            mouse_scroll: self.mouse_scroll,
+            mouse_position: self.mouse_position,
+            key_events: self.key_events.clone(),
+            click_events: self.click_events.clone(),
+            resize_events: self.resize_events.clone(),
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.modifers = source.modifers;
+        self.mouse_scroll = source.mouse_scroll;
+        self.mouse_position = source.mouse_position;
+        self.key_events.clone_from(&source.key_events);
+        self.click_events.clone_from(&source.click_events);
+        self.resize_events.clone_from(&source.resize_events);
+    }
+}
+
+impl Default for SurfaceEdgeData {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Flushable for SurfaceEdgeData {
@@ -12,7 +12,7 @@ use winit::{
 pub struct WindowCont {
     window: Window,
     event_loop: EventLoop<()>,
-    surface_edge: Manual<Arc<CopySwap<SurfaceEdgeData>>>,
+    surface_edge: Manual<Arc<TripleBuffer<SurfaceEdgeData>>>,
     window_closed: Arc<RwLock<bool>>,
 }
 
@@ -31,7 +31,7 @@ impl WindowCont {
 }
 
 impl Surface for WindowCont {
-    fn set_window_edge(&mut self, edge: &Arc<CopySwap<SurfaceEdgeData>>) {
+    fn set_window_edge(&mut self, edge: &Arc<TripleBuffer<SurfaceEdgeData>>) {
         self.surface_edge.init(Arc::clone(edge));
     }
 
@@ -63,7 +63,7 @@ impl Surface for WindowCont {
             let surface_edge = &surface_edge;
             let window_closed = &window_closed;
 
-            let mut edge = surface_edge.get_mut();
+            let mut edge = surface_edge.write();
             edge.mouse_scroll = MouseScrollState {
                 delta_x: 0.0,
                 delta_y: 0.0,
@@ -134,13 +134,16 @@ impl Surface for WindowCont {
                             ElementState::Released => PressState::Up,
                         };
                         if let Some(virtual_keycode) = input.virtual_keycode {
-                            let keycode = unsafe { std::mem::transmute(virtual_keycode as u32) };
+                            let keycode = unsafe {
+                                std::mem::transmute::<u32, VirtualKeyCode>(virtual_keycode as u32)
+                            };
                             edge.key_events.push(KeyEvent { press, keycode })
                         };
                     }
                     _ => {}
                 },
                 Event::MainEventsCleared => {
+                    edge.publish();
                     control_flow.set_exit();
                 }
                 _ => {}
@@ -0,0 +1,100 @@
+use raes::base::*;
+
+struct Sprites;
+impl Cont for Sprites {}
+
+fn setup() -> (ContEntities<Sprites, 8>, Row<u32, 8>, Vec<Entity<Sprites>>) {
+    let mut entities = ContEntities::new();
+    let row = Row::new(0).with_change_tracking();
+    let sprites = (0..4).map(|_| entities.spawn()).collect();
+    (entities, row, sprites)
+}
+
+#[test]
+fn nothing_changed_initially() {
+    let (entities, row, _) = setup();
+    assert!(row.is_change_tracked());
+    assert_eq!(row.changed(&entities).count(), 0);
+}
+
+#[test]
+fn get_mut_marks_changed() {
+    let (entities, mut row, sprites) = setup();
+    *row.get_mut(sprites[2]) = 1;
+    assert_eq!(row.changed(&entities).collect::<Vec<_>>(), vec![sprites[2]]);
+}
+
+#[test]
+fn flush_clears_changed_but_not_changed_since() {
+    let (entities, mut row, sprites) = setup();
+    let before = row.tick();
+    *row.get_mut(sprites[1]) = 1;
+    row.flush();
+    assert_eq!(row.tick(), before + 1);
+    assert_eq!(row.changed(&entities).count(), 0);
+    assert_eq!(
+        row.changed_since(&entities, before).collect::<Vec<_>>(),
+        vec![sprites[1]]
+    );
+
+    *row.get_mut(sprites[3]) = 1;
+    assert_eq!(row.changed(&entities).collect::<Vec<_>>(), vec![sprites[3]]);
+    assert_eq!(
+        row.changed_since(&entities, before).collect::<Vec<_>>(),
+        vec![sprites[1], sprites[3]]
+    );
+}
+
+#[test]
+fn changed_skips_despawned_entities() {
+    let (mut entities, mut row, sprites) = setup();
+    *row.get_mut(sprites[0]) = 1;
+    entities.despawn(sprites[0]).unwrap();
+    assert_eq!(row.changed(&entities).count(), 0);
+}
+
+#[test]
+fn query_only_marks_written_items() {
+    let (entities, mut row, sprites) = setup();
+    for (sprite, mut value) in entities.query((&mut row,)) {
+        if sprite == sprites[1] {
+            *value += 1;
+        } else {
+            assert_eq!(*value, 0);
+        }
+    }
+    assert_eq!(row.changed(&entities).collect::<Vec<_>>(), vec![sprites[1]]);
+}
+
+#[test]
+fn query_rejected_by_later_column_is_not_marked() {
+    let (entities, mut row, _) = setup();
+    let sparse = SparseRow::<u32>::new();
+    assert_eq!(entities.query((&mut row, &sparse)).count(), 0);
+    assert_eq!(row.changed(&entities).count(), 0);
+}
+
+#[test]
+fn as_mut_slice_marks_every_slot() {
+    let (entities, mut row, sprites) = setup();
+    row.as_mut_slice()[0] = 1;
+    assert_eq!(row.changed(&entities).collect::<Vec<_>>(), sprites);
+}
+
+#[test]
+fn deserialized_change_ticks_are_fitted_to_the_slots() {
+    let (entities, _, sprites) = setup();
+    for ron in [
+        "(datas: [0, 0, 0, 0, 0, 0, 0, 0], init_val: 0, changes: Some([]), tick: 1)",
+        "(datas: [0, 0, 0, 0], init_val: 0, changes: Some([0, 0, 0, 0, 0, 0]), tick: 1)",
+    ] {
+        let mut row: Row<u32, 8> = ron::from_str(ron).unwrap();
+        assert_eq!(row.changed(&entities).count(), 0);
+        for (sprite, mut value) in entities.query((&mut row,)) {
+            if sprite == sprites[3] {
+                *value = 1;
+            }
+        }
+        assert_eq!(row.changed(&entities).collect::<Vec<_>>(), vec![sprites[3]]);
+    }
+}
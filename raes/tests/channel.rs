@@ -0,0 +1,103 @@
+use raes::base::*;
+use std::sync::Arc;
+
+fn read(channel: &EventChannel<u32>, reader: &mut EventReader<u32>) -> Vec<u32> {
+    channel.read(reader).into_iter().collect()
+}
+
+#[test]
+fn readers_start_at_the_events_sent_after_them() {
+    let channel = EventChannel::default();
+    channel.send(1);
+    let mut reader = channel.reader();
+    assert!(!reader.has_unread(&channel));
+
+    channel.send_batch([2, 3]);
+    assert!(reader.has_unread(&channel));
+    assert_eq!(read(&channel, &mut reader), [2, 3]);
+    assert!(!reader.has_unread(&channel));
+    assert!(channel.read(&mut reader).is_empty());
+}
+
+#[test]
+fn readers_keep_their_own_cursors() {
+    let channel = EventChannel::default();
+    let mut fast = channel.reader();
+    let mut slow = channel.reader();
+
+    channel.send(1);
+    assert_eq!(read(&channel, &mut fast), [1]);
+    channel.send(2);
+    assert_eq!(read(&channel, &mut fast), [2]);
+
+    let mut copy = slow.clone();
+    assert_eq!(read(&channel, &mut slow), [1, 2]);
+    assert_eq!(read(&channel, &mut copy), [1, 2]);
+}
+
+#[test]
+fn events_expire_after_their_lifetime() {
+    let channel = EventChannel::new(2);
+    let mut reader = channel.reader();
+    channel.send(1);
+    channel.end_frame();
+    channel.send(2);
+    assert_eq!(channel.len(), 2);
+
+    //  A reader that runs before the senders of the next frame still sees the event.
+    let mut early = reader.clone();
+    assert_eq!(read(&channel, &mut early), [1, 2]);
+
+    channel.end_frame();
+    assert_eq!(channel.frame(), 2);
+    assert_eq!(channel.len(), 1);
+
+    let events = channel.read(&mut reader);
+    assert_eq!(events.iter().copied().collect::<Vec<_>>(), [2]);
+    assert_eq!(events.missed(), 1);
+    assert!(events.lagged());
+
+    channel.end_frame();
+    assert!(channel.is_empty());
+    assert!(!channel.read(&mut reader).lagged());
+}
+
+#[test]
+fn lifetime_is_at_least_one_frame() {
+    let channel = EventChannel::new(0);
+    assert_eq!(channel.lifetime(), 1);
+    let mut reader = channel.reader();
+    channel.send(1);
+    assert_eq!(read(&channel, &mut reader), [1]);
+    Edge::end_frame(&channel);
+    assert!(channel.is_empty());
+}
+
+#[test]
+fn handlers_can_send_while_iterating() {
+    let channel = Arc::new(EventChannel::default());
+    let mut reader = channel.reader();
+    channel.send(1);
+
+    for event in channel.read(&mut reader) {
+        channel.send(event + 10);
+    }
+    assert_eq!(read(&channel, &mut reader), [11]);
+}
+
+#[test]
+#[should_panic(expected = "made by another channel")]
+fn readers_of_other_channels_are_rejected() {
+    let channel = EventChannel::<u32>::default();
+    let other = EventChannel::<u32>::default();
+    let mut reader = other.reader();
+    channel.read(&mut reader);
+}
+
+#[test]
+#[should_panic(expected = "On has_unread")]
+fn has_unread_rejects_readers_of_other_channels() {
+    let channel = EventChannel::<u32>::default();
+    let other = EventChannel::<u32>::default();
+    other.reader().has_unread(&channel);
+}
@@ -0,0 +1,120 @@
+use raes::base::*;
+
+#[derive(Cont)]
+struct Bullets {
+    entities: ContEntities<Self, 8>,
+    speed: Row<u32, 8>,
+}
+
+impl Bullets {
+    fn new() -> Self {
+        Self {
+            entities: ContEntities::new(),
+            speed: Row::new(1),
+        }
+    }
+}
+
+#[test]
+fn spawn_write_and_despawn_through_provisionals() {
+    let mut bullets = Bullets::new();
+    let mut commands = Commands::new();
+
+    let kept = commands.spawn((3,));
+    let gone = commands.spawn((4,));
+    commands.write(kept, |bullets: &mut Bullets, bullet| {
+        *bullets.speed_mut(bullet) = 10
+    });
+    commands.despawn(gone);
+    let spawned = commands.apply(&mut bullets);
+
+    assert_eq!(spawned.len(), 2);
+    let kept = kept.resolve(&spawned).unwrap();
+    let gone = gone.resolve(&spawned).unwrap();
+    assert_eq!(*bullets.speed(kept), 10);
+    assert!(bullets.has_entity(&kept));
+    assert!(!bullets.has_entity(&gone));
+    assert!(commands.is_empty());
+}
+
+#[test]
+fn foreign_provisionals_are_skipped() {
+    let mut bullets = Bullets::new();
+    let mut other = Commands::new();
+    let foreign = other.spawn((5,));
+
+    let mut commands = Commands::new();
+    let own = commands.spawn((6,));
+    commands.despawn(foreign);
+    commands.write(foreign, |bullets: &mut Bullets, bullet| {
+        *bullets.speed_mut(bullet) = 0
+    });
+    let spawned = commands.apply(&mut bullets);
+
+    assert_eq!(foreign.resolve(&spawned), None);
+    let own = own.resolve(&spawned).unwrap();
+    assert!(bullets.has_entity(&own));
+    assert_eq!(*bullets.speed(own), 6);
+}
+
+#[test]
+fn provisionals_of_applied_batches_are_skipped() {
+    let mut bullets = Bullets::new();
+    let mut commands = Commands::new();
+
+    let stale = commands.spawn((7,));
+    let first = commands.apply(&mut bullets);
+    let first = stale.resolve(&first).unwrap();
+
+    let second = commands.spawn((8,));
+    commands.despawn(stale);
+    let spawned = commands.apply(&mut bullets);
+
+    assert_eq!(stale.resolve(&spawned), None);
+    assert!(bullets.has_entity(&first));
+    assert!(bullets.has_entity(&second.resolve(&spawned).unwrap()));
+}
+
+#[test]
+fn writes_to_despawned_entities_are_skipped() {
+    let mut bullets = Bullets::new();
+    let bullet = bullets.spawn_with(2);
+
+    let mut commands = Commands::new();
+    commands.despawn(bullet);
+    commands.write(bullet, |bullets: &mut Bullets, bullet| {
+        *bullets.speed_mut(bullet) = 9
+    });
+    assert_eq!(commands.apply(&mut bullets).into_vec(), vec![]);
+    assert!(!bullets.has_entity(&bullet));
+}
+
+#[test]
+fn spawns_past_capacity_are_skipped_and_reported() {
+    let mut bullets = Bullets::new();
+    for _ in 0..7 {
+        bullets.spawn_with(0);
+    }
+
+    let mut commands = Commands::new();
+    let last = commands.spawn((1,));
+    let overflow = commands.spawn((2,));
+    commands.write(overflow, |bullets: &mut Bullets, bullet| {
+        *bullets.speed_mut(bullet) = 9
+    });
+    let despawned = bullets.entities.iter().next().unwrap();
+    commands.despawn(despawned);
+    let spawned = commands.apply(&mut bullets);
+
+    assert_eq!(spawned.len(), 2);
+    assert_eq!(overflow.resolve(&spawned), None);
+    let last = last.resolve(&spawned).unwrap();
+    assert_eq!(spawned.entities().collect::<Vec<_>>(), vec![last]);
+    assert!(matches!(
+        spawned.failed().collect::<Vec<_>>()[..],
+        [(1, ContError::CapacityExceeded { max: 8, .. })]
+    ));
+    //  Commands after the failed spawn are still applied.
+    assert!(!bullets.has_entity(&despawned));
+    assert!(commands.is_empty());
+}
@@ -0,0 +1,592 @@
+use raes::base::*;
+
+struct Small;
+impl Cont for Small {}
+
+#[derive(Cont)]
+struct Units {
+    entities: ContEntities<Self, 4>,
+    health: Row<u32, 4>,
+    shield: Row<u32, 2>,
+    poisoned: SparseRow<u32>,
+    selected: TagRow,
+}
+
+impl Units {
+    fn new() -> Self {
+        Self {
+            entities: ContEntities::new(),
+            health: Row::new(100),
+            shield: Row::new(0),
+            poisoned: SparseRow::new(),
+            selected: TagRow::default(),
+        }
+    }
+}
+
+#[derive(Cont)]
+struct Swarm {
+    entities: ContEntities<Self, GROWABLE>,
+    speed: Row<u32, GROWABLE>,
+}
+
+impl Swarm {
+    fn new() -> Self {
+        Self {
+            entities: ContEntities::new(),
+            speed: Row::new(1),
+        }
+    }
+}
+
+fn type_name<T>() -> &'static str {
+    std::any::type_name::<T>()
+}
+
+#[test]
+fn try_spawn_capacity_exceeded() {
+    let mut entities = ContEntities::<Small, 2>::new();
+    assert!(entities.try_spawn().is_ok());
+    assert!(entities.try_spawn().is_ok());
+    assert_eq!(
+        entities.try_spawn(),
+        Err(ContError::CapacityExceeded {
+            cont: type_name::<Small>(),
+            max: 2,
+        })
+    );
+}
+
+#[test]
+fn try_spawn_reuses_despawned_slot_when_full() {
+    let mut entities = ContEntities::<Small, 1>::new();
+    let first = entities.spawn();
+    entities.despawn(first).unwrap();
+    let second = entities.try_spawn().unwrap();
+    assert_eq!(second.id(), first.id());
+    assert_eq!(second.generation(), first.generation() + 1);
+}
+
+#[test]
+fn try_spawn_growable_never_exceeds() {
+    let mut entities = ContEntities::<Small, GROWABLE>::new();
+    for _ in 0..1000 {
+        entities.try_spawn().unwrap();
+    }
+    assert_eq!(entities.iter().count(), 1000);
+}
+
+#[test]
+#[should_panic(expected = "On spawn: exceeded max entities of 1 for container")]
+fn spawn_panics_when_full() {
+    let mut entities = ContEntities::<Small, 1>::new();
+    entities.spawn();
+    entities.spawn();
+}
+
+#[test]
+fn try_despawn_live_entity() {
+    let mut entities = ContEntities::<Small, 4>::new();
+    let entity = entities.spawn();
+    assert_eq!(entities.try_despawn(entity), Ok(()));
+    assert!(!entities.has_entity(&entity));
+}
+
+#[test]
+fn try_despawn_twice_is_double_despawn() {
+    let mut entities = ContEntities::<Small, 4>::new();
+    let entity = entities.spawn();
+    entities.try_despawn(entity).unwrap();
+    assert_eq!(
+        entities.try_despawn(entity),
+        Err(ContError::DoubleDespawn {
+            cont: type_name::<Small>(),
+            id: entity.id(),
+            generation: entity.generation(),
+        })
+    );
+    assert_eq!(entities.despawn(entity), None);
+}
+
+#[test]
+fn try_despawn_after_slot_reuse_keeps_new_entity() {
+    let mut entities = ContEntities::<Small, 1>::new();
+    let old = entities.spawn();
+    entities.despawn(old).unwrap();
+    let new = entities.spawn();
+    assert!(matches!(
+        entities.try_despawn(old),
+        Err(ContError::DoubleDespawn { .. })
+    ));
+    assert!(entities.has_entity(&new));
+}
+
+#[test]
+fn try_despawn_foreign_handle_is_stale() {
+    let mut other = ContEntities::<Small, 4>::new();
+    let entity = other.spawn();
+    other.despawn(entity).unwrap();
+    let newer = other.spawn();
+
+    let mut entities = ContEntities::<Small, 4>::new();
+    assert_eq!(
+        entities.try_despawn(newer),
+        Err(ContError::StaleHandle {
+            cont: type_name::<Small>(),
+            id: newer.id(),
+            generation: newer.generation(),
+        })
+    );
+}
+
+#[test]
+fn try_despawn_never_spawned_slot_is_stale() {
+    let mut other = ContEntities::<Small, GROWABLE>::new();
+    let entity = (0..10).map(|_| other.spawn()).last().unwrap();
+
+    let mut entities = ContEntities::<Small, GROWABLE>::new();
+    assert!(matches!(
+        entities.try_despawn(entity),
+        Err(ContError::StaleHandle { .. })
+    ));
+}
+
+#[test]
+fn try_despawn_never_spawned_fixed_slot_is_stale() {
+    let mut other = ContEntities::<Small, 4>::new();
+    let entity = (0..3).map(|_| other.spawn()).last().unwrap();
+    assert_eq!(entity.generation(), 0);
+
+    //  Slot 2 exists in a fixed table but is still dead at generation 0.
+    let mut entities = ContEntities::<Small, 4>::new();
+    entities.spawn();
+    assert_eq!(
+        entities.try_despawn(entity),
+        Err(ContError::StaleHandle {
+            cont: type_name::<Small>(),
+            id: entity.id(),
+            generation: 0,
+        })
+    );
+}
+
+#[test]
+fn check_entity() {
+    let mut entities = ContEntities::<Small, 4>::new();
+    let entity = entities.spawn();
+    assert_eq!(entities.check_entity(&entity), Ok(()));
+    entities.despawn(entity).unwrap();
+    assert!(matches!(
+        entities.check_entity(&entity),
+        Err(ContError::StaleHandle { .. })
+    ));
+}
+
+#[test]
+fn try_get_out_of_range() {
+    let mut entities = ContEntities::<Small, 4>::new();
+    let mut row = Row::<u32, 2>::new(7);
+    let entities = (0..3).map(|_| entities.spawn()).collect::<Vec<_>>();
+
+    assert_eq!(row.try_get(entities[1]), Ok(&7));
+    let out_of_range = ContError::OutOfRange {
+        cont: type_name::<Small>(),
+        row: type_name::<u32>(),
+        id: 2,
+        max: 2,
+    };
+    assert_eq!(row.try_get(entities[2]), Err(out_of_range));
+    assert_eq!(row.try_get_mut(entities[2]), Err(out_of_range));
+}
+
+#[test]
+#[should_panic(expected = "On get: entity id 2 exceeded max entities of 2 for container")]
+fn get_panics_out_of_range() {
+    let mut entities = ContEntities::<Small, 4>::new();
+    let row = Row::<u32, 2>::new(7);
+    let entity = (0..3).map(|_| entities.spawn()).last().unwrap();
+    row.get(entity);
+}
+
+#[test]
+#[should_panic(expected = "On get_mut: entity id 2 exceeded max entities of 2 for container")]
+fn get_mut_panics_out_of_range() {
+    let mut entities = ContEntities::<Small, 4>::new();
+    let mut row = Row::<u32, 2>::new(7);
+    let entity = (0..3).map(|_| entities.spawn()).last().unwrap();
+    row.get_mut(entity);
+}
+
+#[test]
+fn checked_get_stale_handle() {
+    let mut entities = ContEntities::<Small, 4>::new();
+    let mut row = Row::<u32, 4>::new(0);
+    let entity = entities.spawn();
+    *row.checked_get_mut(&entities, entity).unwrap() = 5;
+    assert_eq!(row.checked_get(&entities, entity), Ok(&5));
+
+    entities.despawn(entity).unwrap();
+    assert!(matches!(
+        row.checked_get(&entities, entity),
+        Err(ContError::StaleHandle { .. })
+    ));
+    assert!(matches!(
+        row.checked_get_mut(&entities, entity),
+        Err(ContError::StaleHandle { .. })
+    ));
+}
+
+#[test]
+fn checked_get_prefers_stale_over_out_of_range() {
+    let mut entities = ContEntities::<Small, 4>::new();
+    let row = Row::<u32, 1>::new(0);
+    let entity = (0..2).map(|_| entities.spawn()).last().unwrap();
+    assert!(matches!(
+        row.checked_get(&entities, entity),
+        Err(ContError::OutOfRange { .. })
+    ));
+
+    entities.despawn(entity).unwrap();
+    assert!(matches!(
+        row.checked_get(&entities, entity),
+        Err(ContError::StaleHandle { .. })
+    ));
+}
+
+#[test]
+fn derived_try_spawn_with_rolls_back_out_of_range() {
+    let mut units = Units::new();
+    units.try_spawn_with(10, 1).unwrap();
+    units.try_spawn_with(20, 2).unwrap();
+    assert!(matches!(
+        units.try_spawn_with(30, 3),
+        Err(ContError::OutOfRange { id: 2, max: 2, .. })
+    ));
+    assert_eq!(units.entities.iter().count(), 2);
+}
+
+#[test]
+fn derived_try_spawn_with_capacity_exceeded() {
+    let mut units = Units::new();
+    for _ in 0..4 {
+        units.entities.spawn();
+    }
+    assert!(matches!(
+        units.try_spawn_with(0, 0),
+        Err(ContError::CapacityExceeded { max: 4, .. })
+    ));
+}
+
+#[test]
+fn derived_try_spawn_init_and_empty_capacity_exceeded() {
+    let mut units = Units::new();
+    units.try_spawn_init((10, 1)).unwrap();
+    units.try_spawn_empty().unwrap();
+    units.try_spawn_empty().unwrap();
+    let empty = units.try_spawn_empty().unwrap();
+    assert_eq!(*units.health(empty), 100);
+
+    assert!(matches!(
+        units.try_spawn_empty(),
+        Err(ContError::CapacityExceeded { max: 4, .. })
+    ));
+    assert!(matches!(
+        units.try_spawn_init((0, 0)),
+        Err(ContError::CapacityExceeded { max: 4, .. })
+    ));
+    assert_eq!(units.entities.iter().count(), 4);
+}
+
+#[test]
+#[should_panic(expected = "On spawn: exceeded max entities of 4")]
+fn derived_spawn_empty_panics_when_full() {
+    let mut units = Units::new();
+    for _ in 0..5 {
+        units.spawn_empty();
+    }
+}
+
+#[test]
+fn derived_try_despawn_resets_rows() {
+    let mut units = Units::new();
+    let unit = units.spawn_with(10, 1);
+    units.poisoned.insert(unit, 3);
+    units.selected.set(unit);
+
+    assert_eq!(units.try_despawn(unit), Ok(()));
+    assert!(matches!(
+        units.try_despawn(unit),
+        Err(ContError::DoubleDespawn { .. })
+    ));
+    assert_eq!(units.despawn(unit), None);
+
+    let reused = units.spawn_with(100, 0);
+    assert_eq!(reused.id(), unit.id());
+    assert_eq!(*units.shield(reused), 0);
+    assert!(units.poisoned.get(reused).is_none());
+    assert!(!units.selected.test(reused));
+}
+
+#[test]
+fn error_messages() {
+    let error = ContError::DoubleDespawn {
+        cont: "Units",
+        id: 3,
+        generation: 1,
+    };
+    assert_eq!(
+        error.to_string(),
+        "entity 3v1 of container Units was already despawned."
+    );
+}
+
+#[test]
+fn manual_try_init() {
+    let mut manual = Manual::<u32>::default();
+    assert!(!manual.is_initialized());
+    assert_eq!(manual.try_init(1), Ok(()));
+    assert_eq!(
+        manual.try_init(2),
+        Err(ManualError::AlreadyInitialized(type_name::<u32>()))
+    );
+    assert_eq!(*manual, 1);
+}
+
+#[test]
+fn manual_try_get_and_take() {
+    let mut manual = Manual::<u32>::default();
+    let not_initialized = ManualError::NotInitialized(type_name::<u32>());
+    assert_eq!(manual.try_get(), Err(not_initialized));
+    assert_eq!(manual.try_get_mut(), Err(not_initialized));
+    assert_eq!(manual.try_take(), Err(not_initialized));
+
+    manual.init(1);
+    *manual.try_get_mut().unwrap() += 1;
+    assert_eq!(manual.try_get(), Ok(&2));
+    assert_eq!(manual.try_take(), Ok(2));
+    assert_eq!(manual.try_take(), Err(not_initialized));
+
+    manual.init(3);
+    assert_eq!(*manual, 3);
+}
+
+#[test]
+#[should_panic(expected = "initialized twice.")]
+fn manual_init_twice_panics() {
+    let mut manual = Manual::<u32>::default();
+    manual.init(1);
+    manual.init(2);
+}
+
+#[test]
+#[should_panic(expected = "nothing to take.")]
+fn manual_take_empty_panics() {
+    Manual::<u32>::default().take();
+}
+
+#[test]
+#[should_panic(expected = "is not initialized.")]
+fn manual_deref_empty_panics() {
+    let manual = Manual::<u32>::default();
+    let _ = *manual;
+}
+
+#[test]
+fn growable_spawns_past_default_capacity() {
+    let mut swarm = Swarm::new();
+    let bees = (0..1000).map(|i| swarm.spawn_with(i)).collect::<Vec<_>>();
+    assert_eq!(swarm.entities.capacity(), 1000);
+    assert_eq!(swarm.speed.as_slice().len(), swarm.entities.capacity());
+    for (i, &bee) in bees.iter().enumerate() {
+        assert_eq!(*swarm.speed(bee), i as u32);
+        assert_eq!(swarm.speed.as_slice()[bee.id()], i as u32);
+    }
+}
+
+#[test]
+fn growable_derived_rows_keep_pace_with_spawn_empty() {
+    let mut swarm = Swarm::new();
+    let bees = (0..200).map(|_| swarm.spawn_empty()).collect::<Vec<_>>();
+    assert_eq!(swarm.speed.as_slice().len(), swarm.entities.capacity());
+    assert!(bees.iter().all(|bee| swarm.speed.as_slice()[bee.id()] == 1));
+}
+
+#[test]
+fn growable_standalone_row_grows_to_fit() {
+    let mut entities = ContEntities::<Small, GROWABLE>::new();
+    let mut row = Row::<u32, GROWABLE>::new(7);
+    let all = (0..300).map(|_| entities.spawn()).collect::<Vec<_>>();
+    assert_eq!(*row.get(all[299]), 7);
+    assert!(row.as_slice().is_empty());
+
+    *row.get_mut(all[10]) = 1;
+    assert_eq!(row.as_slice().len(), 11);
+
+    row.grow_to_fit(&entities);
+    assert_eq!(row.as_slice().len(), entities.capacity());
+    assert_eq!(row.as_slice()[10], 1);
+    assert_eq!(row.as_slice()[299], 7);
+}
+
+#[test]
+fn spawn_hands_out_lowest_ids_first() {
+    let mut entities = ContEntities::<Small, 4>::new();
+    let ids = (0..4).map(|_| entities.spawn().id()).collect::<Vec<_>>();
+    assert_eq!(ids, vec![0, 1, 2, 3]);
+}
+
+#[test]
+fn spawn_reuses_most_recently_freed_slot() {
+    let mut entities = ContEntities::<Small, 4>::new();
+    let all = (0..4).map(|_| entities.spawn()).collect::<Vec<_>>();
+    entities.despawn(all[1]).unwrap();
+    entities.despawn(all[3]).unwrap();
+    entities.despawn(all[0]).unwrap();
+
+    let reused = (0..3).map(|_| entities.spawn()).collect::<Vec<_>>();
+    assert_eq!(
+        reused.iter().map(|entity| entity.id()).collect::<Vec<_>>(),
+        vec![0, 3, 1]
+    );
+    assert!(reused.iter().all(|entity| entity.generation() == 1));
+}
+
+fn entities_at_max_generation<const MAX_ENTITIES: usize>() -> ContEntities<Small, MAX_ENTITIES> {
+    ron::from_str(&format!(
+        "(entities: [(({}), (false))], free: [0])",
+        usize::MAX
+    ))
+    .unwrap()
+}
+
+#[test]
+fn generation_overflow_retires_slot() {
+    let mut entities = entities_at_max_generation::<1>();
+    let last = entities.spawn();
+    assert_eq!(last.generation(), usize::MAX);
+
+    entities.despawn(last).unwrap();
+    assert!(!entities.has_entity(&last));
+    assert!(matches!(
+        entities.try_spawn(),
+        Err(ContError::CapacityExceeded { max: 1, .. })
+    ));
+    assert!(matches!(
+        entities.try_despawn(last),
+        Err(ContError::DoubleDespawn { .. })
+    ));
+}
+
+#[test]
+fn generation_overflow_growable_moves_to_new_slot() {
+    let mut entities = entities_at_max_generation::<GROWABLE>();
+    let last = entities.spawn();
+    entities.despawn(last).unwrap();
+
+    let next = entities.spawn();
+    assert_eq!(next.id(), 1);
+    assert_eq!(next.generation(), 0);
+    assert!(!entities.has_entity(&last));
+}
+
+#[test]
+fn serde_round_trip_keeps_generations_and_free_list() {
+    let mut entities = ContEntities::<Small, 4>::new();
+    let mut health = Row::<u32, 4>::new(100);
+    let all = (0..4).map(|_| entities.spawn()).collect::<Vec<_>>();
+    *health.get_mut(all[2]) = 42;
+    entities.despawn(all[1]).unwrap();
+    entities.despawn(all[3]).unwrap();
+    let recycled = entities.spawn();
+    entities.despawn(recycled).unwrap();
+
+    let mut loaded: ContEntities<Small, 4> =
+        ron::from_str(&ron::to_string(&entities).unwrap()).unwrap();
+    let loaded_health: Row<u32, 4> = ron::from_str(&ron::to_string(&health).unwrap()).unwrap();
+
+    assert_eq!(loaded.iter().collect::<Vec<_>>(), vec![all[0], all[2]]);
+    assert_eq!(*loaded_health.get(all[2]), 42);
+    for stale in [all[1], all[3], recycled] {
+        assert!(!loaded.has_entity(&stale));
+        assert!(loaded.try_despawn(stale).is_err());
+    }
+    assert_eq!(loaded.spawn(), entities.spawn());
+    assert_eq!(loaded.spawn(), entities.spawn());
+}
+
+fn load_small<const MAX_ENTITIES: usize>(
+    ron: &str,
+) -> Result<ContEntities<Small, MAX_ENTITIES>, ron::error::SpannedError> {
+    ron::from_str(ron)
+}
+
+#[test]
+fn deserialize_rejects_inconsistent_tables() {
+    let cases = [
+        ("(entities: [((0), (true))], free: [7])", "out of range"),
+        ("(entities: [((0), (true))], free: [0])", "is alive"),
+        ("(entities: [((0), (false))], free: [0, 0])", "listed twice"),
+        (
+            "(entities: [((3), (false))], free: [])",
+            "missing from the free list",
+        ),
+    ];
+    for (ron, message) in cases {
+        let error = load_small::<GROWABLE>(ron).err().unwrap().to_string();
+        assert!(error.contains(message), "{}: {}", ron, error);
+    }
+
+    let error = load_small::<2>("(entities: [((0), (false))], free: [0])")
+        .err()
+        .unwrap()
+        .to_string();
+    assert!(error.contains("has 1 slots instead of 2"), "{}", error);
+}
+
+#[test]
+fn deserialize_accepts_retired_slots() {
+    let ron = format!(
+        "(entities: [(({}), (false)), ((0), (false))], free: [1])",
+        usize::MAX
+    );
+    let mut entities = load_small::<2>(&ron).unwrap();
+    assert_eq!(entities.spawn().id(), 1);
+    assert!(entities.try_spawn().is_err());
+}
+
+#[test]
+fn deserialize_rejects_inconsistent_sparse_rows() {
+    let cases = [
+        (
+            "(sparse: [Some(0)], keys: [], datas: [1])",
+            "0 keys for 1 values",
+        ),
+        (
+            "(sparse: [Some(1)], keys: [(0, 0)], datas: [1])",
+            "out of range",
+        ),
+        (
+            "(sparse: [Some(0), Some(0)], keys: [(0, 0)], datas: [1])",
+            "used twice",
+        ),
+        (
+            "(sparse: [None, Some(0)], keys: [(0, 0)], datas: [1])",
+            "keyed by id 0",
+        ),
+        ("(sparse: [], keys: [(0, 0)], datas: [1])", "has no id"),
+    ];
+    for (ron, message) in cases {
+        let error = ron::from_str::<SparseRow<u32>>(ron)
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(error.contains(message), "{}: {}", ron, error);
+    }
+
+    let mut entities = ContEntities::<Small, 2>::new();
+    let (first, second) = (entities.spawn(), entities.spawn());
+    let row: SparseRow<u32> =
+        ron::from_str("(sparse: [None, Some(0)], keys: [(1, 0)], datas: [7])").unwrap();
+    assert_eq!(row.get(first), None);
+    assert_eq!(row.get(second), Some(&7));
+}
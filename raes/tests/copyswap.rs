@@ -0,0 +1,90 @@
+use raes::base::*;
+
+#[derive(Clone, Copy, Default)]
+struct Counter {
+    value: u32,
+    flushes: u32,
+}
+
+impl Flushable for Counter {
+    fn flush(&mut self) {
+        self.flushes += 1;
+    }
+}
+
+#[derive(Default)]
+struct Log {
+    lines: Vec<String>,
+}
+
+/// Derived `Clone` does not forward `clone_from`, which is what reuses the allocations.
+impl Clone for Log {
+    fn clone(&self) -> Self {
+        Self {
+            lines: self.lines.clone(),
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.lines.clone_from(&source.lines);
+    }
+}
+
+impl Flushable for Log {}
+
+#[test]
+fn flush_publishes_copy_data() {
+    let mut swap = CopySwap::new(Counter::default());
+    swap.get_mut().value = 3;
+    assert_eq!(swap.get().value, 0);
+
+    swap.flush();
+    assert_eq!(swap.get().value, 3);
+    assert_eq!(swap.get().flushes, 1);
+
+    //  The write buffer starts from the published data.
+    swap.get_mut().value += 1;
+    swap.flush();
+    assert_eq!(swap.get().value, 4);
+    assert_eq!(swap.get().flushes, 2);
+}
+
+#[test]
+fn flush_clones_heap_data() {
+    let mut swap = CloneSwap::new(Log::default());
+    swap.get_mut().lines.push("first".to_owned());
+    swap.flush();
+    swap.get_mut().lines.push("second".to_owned());
+
+    assert_eq!(swap.get().lines, ["first"]);
+    assert_eq!(swap.get_mut().lines, ["first", "second"]);
+
+    swap.flush();
+    assert_eq!(swap.get().lines, ["first", "second"]);
+}
+
+#[test]
+fn flush_reuses_the_allocation_of_the_new_write_buffer() {
+    let mut swap = CloneSwap::new(Log::default());
+    //  Both buffers own an allocation after the first flush.
+    swap.get_mut().lines.push("warm up".to_owned());
+    swap.flush();
+    let read_allocation = swap.get().lines.as_ptr();
+    let write_allocation = swap.get_mut().lines.as_ptr();
+
+    for frame in 0..4 {
+        let mut log = swap.get_mut();
+        log.lines.clear();
+        log.lines.push(format!("frame {}", frame));
+        drop(log);
+        swap.flush();
+        let (read, write) = if frame % 2 == 0 {
+            (write_allocation, read_allocation)
+        } else {
+            (read_allocation, write_allocation)
+        };
+        assert_eq!(swap.get().lines.as_ptr(), read);
+        assert_eq!(swap.get_mut().lines.as_ptr(), write);
+        assert_eq!(swap.get_mut().lines, swap.get().lines);
+    }
+}
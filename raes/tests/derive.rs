@@ -0,0 +1,116 @@
+use raes::base::*;
+
+/// Rows named like the locals of the generated code.
+#[derive(Cont)]
+struct Awkward {
+    entities: ContEntities<Self, 4>,
+    entity: Row<u32, 4>,
+    init: Row<u32, 4>,
+    written: Row<u32, 4>,
+    e: Row<u32, 4>,
+}
+
+impl Awkward {
+    fn new() -> Self {
+        Self {
+            entities: ContEntities::new(),
+            entity: Row::new(0),
+            init: Row::new(0),
+            written: Row::new(0),
+            e: Row::new(0),
+        }
+    }
+}
+
+#[derive(Cont)]
+struct Crates {
+    entities: ContEntities<Self, 4>,
+    weight: Row<f32, 4>,
+    label: Row<String, 4>,
+    stickers: SparseRow<Vec<u32>>,
+    opened: TagRow,
+    //  Ignored by the derive.
+    spawned_total: usize,
+}
+
+impl Crates {
+    fn new() -> Self {
+        Self {
+            entities: ContEntities::new(),
+            weight: Row::new(1.0),
+            label: Row::new(String::new()),
+            stickers: SparseRow::new(),
+            opened: TagRow::new(),
+            spawned_total: 0,
+        }
+    }
+}
+
+#[test]
+fn rows_named_like_generated_locals() {
+    let mut awkward = Awkward::new();
+    let spawned = awkward.spawn_with(1, 2, 3, 4);
+    assert_eq!(
+        (
+            *awkward.entity(spawned),
+            *awkward.init(spawned),
+            *awkward.written(spawned),
+            *awkward.e(spawned),
+        ),
+        (1, 2, 3, 4)
+    );
+
+    *awkward.entity_mut(spawned) = 5;
+    assert_eq!(*awkward.entity.get(spawned), 5);
+
+    let from_init = awkward.spawn_init((6, 7, 8, 9));
+    assert_eq!(*awkward.e(from_init), 9);
+
+    awkward.despawn(spawned).unwrap();
+    assert_eq!(*awkward.entity.get(spawned), 0);
+}
+
+#[test]
+fn spawn_with_writes_rows_in_field_order() {
+    let mut crates = Crates::new();
+    let heavy = crates.spawn_with(20.0, String::from("anvils"));
+    crates.spawned_total += 1;
+    assert_eq!(*crates.weight(heavy), 20.0);
+    assert_eq!(crates.label(heavy), "anvils");
+    assert_eq!(crates.spawned_total, 1);
+}
+
+#[test]
+fn despawn_resets_every_row() {
+    let mut crates = Crates::new();
+    let old = crates.spawn_with(20.0, String::from("anvils"));
+    crates.stickers.insert(old, vec![1, 2]);
+    crates.opened.set(old);
+
+    assert_eq!(crates.despawn(old), Some(()));
+    assert_eq!(crates.despawn(old), None);
+    assert!(!crates.has_entity(&old));
+
+    let new = crates.spawn_empty();
+    assert_eq!(new.id(), old.id());
+    assert_eq!(*crates.weight(new), 1.0);
+    assert_eq!(crates.label(new), "");
+    assert!(crates.stickers.get(new).is_none());
+    assert!(!crates.opened.test(new));
+}
+
+#[test]
+fn flush_advances_rows() {
+    let mut crates = Crates::new();
+    crates.entities.buffer_lifecycle(EventBufferConfig {
+        capacity: 4,
+        overflow: Overflow::DropOldest,
+    });
+    let tick = crates.weight.tick();
+    crates.spawn_with(1.0, String::new());
+    assert_eq!(crates.entities.spawned().unwrap().len(), 1);
+
+    crates.flush();
+    assert_eq!(crates.weight.tick(), tick + 1);
+    assert!(crates.entities.spawned().unwrap().is_empty());
+}
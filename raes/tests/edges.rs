@@ -0,0 +1,129 @@
+use raes::base::*;
+
+#[derive(Clone, Default)]
+struct Score {
+    points: u32,
+    flushes: u32,
+}
+
+impl Flushable for Score {
+    fn flush(&mut self) {
+        self.flushes += 1;
+    }
+}
+
+#[derive(Clone, Default)]
+struct Input {
+    presses: u32,
+}
+
+impl Flushable for Input {}
+
+#[test]
+fn published_edges_become_readable_after_the_frame_ends() {
+    let mut edges = Edges::new();
+    let producer = edges.publish(TripleBuffer::new(Score::default())).unwrap();
+    let subscriber = edges.subscribe::<TripleBuffer<Score>>().unwrap();
+
+    producer.write().points = 5;
+    assert_eq!(subscriber.read().points, 0);
+
+    edges.end_frame();
+    assert_eq!(subscriber.read().points, 5);
+    //  The producer continues from a flushed copy of the published frame.
+    assert_eq!(producer.write().flushes, 1);
+}
+
+#[test]
+fn end_frame_flushes_locked_edges_in_publish_order() {
+    let mut edges = Edges::new();
+    let score = edges.publish(RwLock::new(Score::default())).unwrap();
+    edges
+        .publish_arc(Arc::new(Mutex::new(Input::default())))
+        .unwrap();
+
+    edges.end_frame();
+    edges.end_frame();
+    assert_eq!(score.read().flushes, 2);
+    assert!(edges.has::<Mutex<Input>>());
+}
+
+#[test]
+fn unmanaged_edges_are_left_to_their_producer() {
+    let mut edges = Edges::new();
+    let producer = edges
+        .publish_unmanaged(TripleBuffer::new(Input::default()))
+        .unwrap();
+    let subscriber = edges.subscribe::<TripleBuffer<Input>>().unwrap();
+
+    producer.write().presses = 2;
+    edges.end_frame();
+    assert_eq!(subscriber.read().presses, 0);
+
+    producer.publish();
+    assert_eq!(subscriber.read().presses, 2);
+}
+
+#[test]
+fn clones_share_the_edges_published_so_far() {
+    let mut edges = Edges::new();
+    let producer = edges.publish(TripleBuffer::new(Score::default())).unwrap();
+    let frame_loop = edges.clone();
+
+    producer.write().points = 1;
+    frame_loop.end_frame();
+    assert_eq!(
+        edges
+            .subscribe::<TripleBuffer<Score>>()
+            .unwrap()
+            .read()
+            .points,
+        1
+    );
+}
+
+#[test]
+fn subscribing_needs_exactly_one_producer() {
+    let mut edges = Edges::new();
+    assert!(matches!(
+        edges.subscribe::<RwLock<Score>>(),
+        Err(EngineError::EdgeMissingProducer(_))
+    ));
+
+    edges.publish(RwLock::new(Score::default())).unwrap();
+    assert!(matches!(
+        edges.publish_unmanaged(RwLock::new(Score::default())),
+        Err(EngineError::EdgeAlreadyPublished(_))
+    ));
+}
+
+/// Scores a point every frame of its loop.
+#[derive(Inject)]
+struct Scoring {
+    frames: u32,
+    score: Arc<TripleBuffer<Score>>,
+}
+
+impl Scene for Scoring {
+    fn run(&mut self, _icebox: IceBox, mut frames: Frames) -> anyhow::Result<SceneExit> {
+        for _ in 0..self.frames {
+            self.score.write().points += 1;
+            frames.end_frame();
+        }
+        assert_eq!(frames.frame(), self.frames as u64);
+        Ok(SceneExit::End)
+    }
+}
+
+#[test]
+fn scenes_end_frames_through_their_frames_handle() {
+    let mut edges = Edges::new();
+    let score = edges.publish(TripleBuffer::new(Score::default())).unwrap();
+    let subscriber = edges.subscribe::<TripleBuffer<Score>>().unwrap();
+
+    let mut scene = Scoring { frames: 3, score };
+    scene
+        .run(IceBox::new(), Frames::new(edges.clone()))
+        .unwrap();
+    assert_eq!(subscriber.read().points, 3);
+}
@@ -0,0 +1,159 @@
+use raes::{base::*, surface::*};
+
+fn filled(
+    capacity: usize,
+    overflow: Overflow,
+    events: impl IntoIterator<Item = u32>,
+) -> EventBuffer<u32> {
+    let mut buffer = EventBuffer::new(capacity, overflow);
+    for event in events {
+        buffer.push(event);
+    }
+    buffer
+}
+
+fn events(buffer: &EventBuffer<u32>) -> Vec<u32> {
+    buffer.iter().copied().collect()
+}
+
+#[test]
+fn pushed_events_are_kept_in_order() {
+    //  Events used to be dropped without ever being counted.
+    let buffer = filled(4, Overflow::DropNewest, [1, 2, 3]);
+    assert_eq!(buffer.len(), 3);
+    assert_eq!(events(&buffer), [1, 2, 3]);
+    assert_eq!(buffer.dropped(), 0);
+    assert_eq!(buffer.spilled(), 0);
+}
+
+#[test]
+fn drop_newest_keeps_the_first_events() {
+    let mut buffer = filled(2, Overflow::DropNewest, 1..=5);
+    assert_eq!(events(&buffer), [1, 2]);
+    assert_eq!(buffer.dropped(), 3);
+    assert_eq!(buffer.total_dropped(), 3);
+
+    buffer.flush();
+    assert!(buffer.is_empty());
+    assert_eq!(buffer.dropped(), 0);
+    assert_eq!(buffer.total_dropped(), 3);
+
+    buffer.push(6);
+    buffer.push(7);
+    buffer.push(8);
+    assert_eq!(events(&buffer), [6, 7]);
+    assert_eq!(buffer.dropped(), 1);
+    assert_eq!(buffer.total_dropped(), 4);
+}
+
+#[test]
+fn drop_oldest_keeps_the_last_events() {
+    let buffer = filled(3, Overflow::DropOldest, 1..=5);
+    assert_eq!(events(&buffer), [3, 4, 5]);
+    assert_eq!(buffer.dropped(), 2);
+
+    let (front, back) = buffer.as_slices();
+    assert_eq!([front, back].concat(), [3, 4, 5]);
+}
+
+#[test]
+fn zero_capacity_drops_every_event() {
+    for overflow in [Overflow::DropNewest, Overflow::DropOldest] {
+        let buffer = filled(0, overflow, 1..=2);
+        assert!(buffer.is_empty());
+        assert_eq!(buffer.dropped(), 2);
+    }
+}
+
+#[test]
+fn spill_keeps_every_event_past_capacity() {
+    let mut buffer = filled(2, Overflow::Spill, 1..=5);
+    assert_eq!(events(&buffer), [1, 2, 3, 4, 5]);
+    assert_eq!(buffer.spilled(), 3);
+    assert_eq!(buffer.dropped(), 0);
+    assert_eq!(buffer.capacity(), 2);
+
+    buffer.flush();
+    assert_eq!(buffer.spilled(), 0);
+}
+
+#[test]
+fn clone_from_copies_events_and_counters() {
+    let source = filled(2, Overflow::DropOldest, 1..=3);
+    let mut copy = filled(8, Overflow::Spill, [9]);
+    copy.clone_from(&source);
+    assert_eq!(events(&copy), [2, 3]);
+    assert_eq!(copy.capacity(), 2);
+    assert_eq!(copy.overflow(), Overflow::DropOldest);
+    assert_eq!(copy.dropped(), 1);
+    assert_eq!(copy.total_dropped(), 1);
+
+    let clone = source.clone();
+    assert_eq!(events(&clone), [2, 3]);
+    assert_eq!(clone.dropped(), 1);
+}
+
+#[test]
+fn configs_default_to_drop_newest() {
+    let config: EventBufferConfig = ron::from_str("(capacity: 4)").unwrap();
+    assert_eq!(config.overflow, Overflow::DropNewest);
+    let buffer = EventBuffer::<u32>::from_config(config);
+    assert_eq!(buffer.capacity(), 4);
+    assert_eq!(buffer.overflow(), Overflow::DropNewest);
+}
+
+#[test]
+fn surface_buffers_are_configured_by_the_engine() {
+    let engine = Engine::from_config_str(
+        r#"EngineConfig(
+            load_scene: "hello.ron",
+            event_buffers: {
+                "surface.key_events": (capacity: 32, overflow: DropOldest),
+                "surface.resize_events": (capacity: 1, overflow: Spill),
+            },
+        )"#,
+    )
+    .unwrap();
+    assert_eq!(
+        engine.event_buffer_config("surface.key_events"),
+        Some(EventBufferConfig {
+            capacity: 32,
+            overflow: Overflow::DropOldest,
+        })
+    );
+    assert_eq!(engine.event_buffer_config("surface.click_events"), None);
+
+    let data = SurfaceEdgeData::from_engine(&engine);
+    assert_eq!(data.key_events().capacity(), 32);
+    assert_eq!(data.key_events().overflow(), Overflow::DropOldest);
+    assert_eq!(data.resize_events().capacity(), 1);
+    assert_eq!(data.resize_events().overflow(), Overflow::Spill);
+
+    let defaults = SurfaceEdgeData::new();
+    assert_eq!(
+        data.click_events().capacity(),
+        defaults.click_events().capacity()
+    );
+    assert_eq!(
+        data.click_events().overflow(),
+        defaults.click_events().overflow()
+    );
+}
+
+#[test]
+fn engine_configs_without_event_buffers_use_the_defaults() {
+    let engine = Engine::from_config_str(r#"EngineConfig(load_scene: "hello.ron")"#).unwrap();
+    assert_eq!(engine.get_first_scene(), "hello.ron");
+    assert_eq!(engine.event_buffer_config("surface.key_events"), None);
+
+    let data = SurfaceEdgeData::from_engine(&engine);
+    assert_eq!(
+        data.key_events().capacity(),
+        SurfaceEdgeData::new().key_events().capacity()
+    );
+
+    assert!(matches!(
+        Engine::from_config_str("EngineConfig("),
+        Err(EngineError::ParseConfig(_))
+    ));
+}
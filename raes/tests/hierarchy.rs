@@ -0,0 +1,114 @@
+use raes::base::*;
+
+#[derive(Cont)]
+struct Nodes {
+    entities: ContEntities<Self, 8>,
+    health: Row<u32, 8>,
+    burning: SparseRow<u32>,
+    selected: TagRow,
+}
+
+impl Nodes {
+    fn new() -> Self {
+        Self {
+            entities: ContEntities::new(),
+            health: Row::new(100),
+            burning: SparseRow::new(),
+            selected: TagRow::new(),
+        }
+    }
+}
+
+/// A root with two children, the first of which has a child of its own.
+fn tree(nodes: &mut Nodes, hierarchy: &mut Hierarchy<Nodes>) -> [Entity<Nodes>; 4] {
+    let all = [0, 1, 2, 3].map(|_| nodes.spawn_with(7));
+    hierarchy.set_parent(all[1], all[0]).unwrap();
+    hierarchy.set_parent(all[2], all[0]).unwrap();
+    hierarchy.set_parent(all[3], all[1]).unwrap();
+    for &node in &all {
+        nodes.burning.insert(node, 1);
+        nodes.selected.set(node);
+    }
+    all
+}
+
+#[test]
+fn despawn_recursive_resets_rows_of_descendants() {
+    let mut nodes = Nodes::new();
+    let mut hierarchy = Hierarchy::new();
+    let all = tree(&mut nodes, &mut hierarchy);
+
+    assert_eq!(hierarchy.despawn_recursive(&mut nodes, all[0]), Some(()));
+    assert_eq!(nodes.entities.iter().count(), 0);
+    assert!(nodes.burning.is_empty());
+    assert_eq!(nodes.selected.count(), 0);
+
+    for _ in 0..4 {
+        let reused = nodes.spawn_empty();
+        assert_eq!(*nodes.health(reused), 100);
+    }
+}
+
+#[test]
+fn despawn_recursive_keeps_other_branches() {
+    let mut nodes = Nodes::new();
+    let mut hierarchy = Hierarchy::new();
+    let all = tree(&mut nodes, &mut hierarchy);
+
+    hierarchy.despawn_recursive(&mut nodes, all[1]).unwrap();
+    assert_eq!(
+        nodes.entities.iter().collect::<Vec<_>>(),
+        vec![all[0], all[2]]
+    );
+    assert_eq!(hierarchy.children(all[0]), &[all[2]]);
+    assert_eq!(hierarchy.parent(all[3]), None);
+}
+
+#[test]
+fn despawn_recursive_of_dead_entity_despawns_nothing() {
+    let mut nodes = Nodes::new();
+    let mut hierarchy = Hierarchy::new();
+    let all = tree(&mut nodes, &mut hierarchy);
+    nodes.entities.despawn(all[1]).unwrap();
+
+    assert_eq!(hierarchy.despawn_recursive(&mut nodes, all[1]), None);
+    assert!(nodes.entities.has_entity(&all[3]));
+    assert_eq!(hierarchy.parent(all[3]), Some(all[1]));
+}
+
+#[test]
+fn despawn_recursive_with_standalone_entities() {
+    struct Bones;
+    impl Cont for Bones {}
+
+    let mut entities = ContEntities::<Bones, 4>::new();
+    let mut hierarchy = Hierarchy::new();
+    let root = entities.spawn();
+    let child = entities.spawn();
+    hierarchy.set_parent(child, root).unwrap();
+
+    let mut despawned = vec![];
+    hierarchy
+        .despawn_recursive_with(root, |bone| {
+            despawned.push(bone);
+            entities.despawn(bone)
+        })
+        .unwrap();
+    assert_eq!(despawned, vec![root, child]);
+    assert_eq!(entities.iter().count(), 0);
+}
+
+#[test]
+fn set_parent_rejects_cycles() {
+    let mut nodes = Nodes::new();
+    let mut hierarchy = Hierarchy::new();
+    let all = tree(&mut nodes, &mut hierarchy);
+    assert_eq!(
+        hierarchy.set_parent(all[0], all[3]),
+        Err(HierarchyError::Cycle)
+    );
+    assert_eq!(
+        hierarchy.set_parent(all[0], all[0]),
+        Err(HierarchyError::Cycle)
+    );
+}
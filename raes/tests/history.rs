@@ -0,0 +1,154 @@
+use cgmath::Vector2;
+use raes::base::*;
+use std::time::Duration;
+
+#[derive(Clone, Default)]
+struct Body {
+    x: f32,
+    events: Vec<u32>,
+}
+
+impl Flushable for Body {
+    fn flush(&mut self) {
+        self.events.clear();
+    }
+}
+
+fn history_of(xs: &[f32], capacity: usize) -> EdgeHistory<Body> {
+    let history = EdgeHistory::new(Body::default(), capacity);
+    for &x in xs {
+        history.write().x = x;
+        history.publish();
+    }
+    history
+}
+
+fn xs(history: &EdgeHistory<Body>) -> Vec<f32> {
+    history
+        .read()
+        .iter()
+        .map(|published| published.data.x)
+        .collect()
+}
+
+#[test]
+fn frames_are_numbered_from_zero() {
+    let history = EdgeHistory::new(Body::default(), 4);
+    assert!(history.read().is_empty());
+    assert_eq!(history.publish(), 0);
+    assert_eq!(history.publish(), 1);
+    assert_eq!(history.publish(), 2);
+
+    let frames = history.read();
+    assert_eq!(frames.latest().unwrap().frame, 2);
+    assert_eq!(frames.previous().unwrap().frame, 1);
+}
+
+#[test]
+fn full_history_overwrites_the_oldest_frame() {
+    let history = history_of(&[0.0, 1.0, 2.0, 3.0, 4.0], 3);
+    assert_eq!(history.capacity(), 3);
+    assert_eq!(xs(&history), [2.0, 3.0, 4.0]);
+
+    let frames = history.read();
+    assert_eq!(frames.len(), 3);
+    let numbers = frames.iter().map(|published| published.frame);
+    assert_eq!(numbers.collect::<Vec<_>>(), [2, 3, 4]);
+}
+
+#[test]
+fn capacity_is_at_least_one() {
+    let history = history_of(&[1.0, 2.0], 0);
+    assert_eq!(history.capacity(), 1);
+    assert_eq!(xs(&history), [2.0]);
+    assert!(history.read().previous().is_none());
+}
+
+#[test]
+fn get_looks_frames_up_by_number() {
+    let history = history_of(&[0.0, 1.0, 2.0, 3.0, 4.0], 3);
+    let frames = history.read();
+    assert!(frames.get(0).is_none());
+    assert!(frames.get(1).is_none());
+    assert_eq!(frames.get(2).unwrap().data.x, 2.0);
+    assert_eq!(frames.get(4).unwrap().data.x, 4.0);
+    assert!(frames.get(5).is_none());
+    assert!(EdgeHistory::new(Body::default(), 2).read().get(0).is_none());
+}
+
+#[test]
+fn writer_continues_from_a_flushed_copy() {
+    let history = EdgeHistory::new(Body::default(), 2);
+    history.write().events.push(7);
+    history.write().x = 3.0;
+    history.publish();
+
+    let write = history.write();
+    assert!(write.events.is_empty());
+    assert_eq!(write.x, 3.0);
+    drop(write);
+    assert_eq!(history.read().latest().unwrap().data.events, [7]);
+}
+
+#[test]
+fn end_frame_publishes_unless_the_writer_is_writing() {
+    let history = EdgeHistory::new(Body::default(), 2);
+    Edge::end_frame(&history);
+    assert_eq!(history.read().len(), 1);
+
+    let write = history.write();
+    Edge::end_frame(&history);
+    drop(write);
+    assert_eq!(history.read().len(), 1);
+}
+
+#[test]
+fn interpolate_blends_previous_towards_latest() {
+    let history = history_of(&[2.0], 4);
+    assert_eq!(history.read().interpolate(0.5, |body| body.x), Some(2.0));
+
+    let history = history_of(&[2.0, 4.0], 4);
+    let frames = history.read();
+    assert_eq!(frames.interpolate(0.0, |body| body.x), Some(2.0));
+    assert_eq!(frames.interpolate(0.25, |body| body.x), Some(2.5));
+    assert_eq!(
+        frames.interpolate(1.0, |body| Vector2::new(body.x, -body.x)),
+        Some(Vector2::new(4.0, -4.0))
+    );
+    assert_eq!(
+        EdgeHistory::new(Body::default(), 1)
+            .read()
+            .interpolate(0.5, |body| body.x),
+        None
+    );
+}
+
+#[test]
+fn interpolate_at_clamps_and_blends_by_publish_time() {
+    let history = EdgeHistory::new(Body::default(), 4);
+    for x in [0.0, 10.0, 20.0] {
+        history.write().x = x;
+        history.publish();
+        std::thread::sleep(Duration::from_millis(20));
+    }
+
+    let frames = history.read();
+    let times = frames
+        .iter()
+        .map(|published| published.time)
+        .collect::<Vec<_>>();
+    let x_at = |time| frames.interpolate_at(time, |body: &Body| body.x).unwrap();
+
+    assert_eq!(x_at(times[0] - Duration::from_millis(5)), 0.0);
+    assert_eq!(x_at(times[0]), 0.0);
+    assert_eq!(x_at(times[1]), 10.0);
+    assert_eq!(x_at(times[2] + Duration::from_secs(1)), 20.0);
+
+    let quarter = times[1] + (times[2] - times[1]) / 4;
+    assert!((x_at(quarter) - 12.5).abs() < 0.01);
+    let half = times[0] + (times[1] - times[0]) / 2;
+    assert!((x_at(half) - 5.0).abs() < 0.01);
+
+    let empty = EdgeHistory::new(Body::default(), 1);
+    assert_eq!(empty.read().interpolate_at(times[0], |body| body.x), None);
+}
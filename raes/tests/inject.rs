@@ -0,0 +1,161 @@
+use raes::base::*;
+
+#[derive(Debug, PartialEq)]
+struct Camera(u32);
+
+impl Preservable for Camera {}
+
+#[derive(Debug, PartialEq)]
+struct Audio;
+
+impl Preservable for Audio {}
+
+type AlsoCamera = Camera;
+
+#[derive(Default, Inject)]
+struct Injected {
+    #[inject(icebox)]
+    camera: Manual<Box<Camera>>,
+    #[inject(icebox)]
+    audio: Manual<Box<Audio>>,
+    #[inject(edge)]
+    channel: Manual<Arc<EventChannel<u32>>>,
+    untouched: u32,
+}
+
+/// The derive cannot tell that an alias names the same type, so this is caught at injection.
+#[derive(Default, Inject)]
+struct Duplicated {
+    #[inject(icebox)]
+    first: Manual<Box<Camera>>,
+    #[inject(icebox)]
+    second: Manual<Box<AlsoCamera>>,
+}
+
+#[derive(Inject)]
+struct Plain;
+
+impl Scene for Plain {
+    fn run(&mut self, _icebox: IceBox, _frames: Frames) -> anyhow::Result<SceneExit> {
+        Ok(SceneExit::End)
+    }
+}
+
+fn icebox_with_camera() -> IceBox {
+    let mut icebox = IceBox::new();
+    icebox.put(Box::new(Camera(3)));
+    icebox
+}
+
+#[test]
+fn marked_fields_are_taken_from_the_icebox_and_edges() {
+    let mut icebox = icebox_with_camera();
+    icebox.put(Box::new(Audio));
+    let mut edges = Edges::new();
+    let channel = edges.publish(EventChannel::<u32>::default()).unwrap();
+
+    let mut scene = Injected::default();
+    scene.inject(&mut icebox, &edges).unwrap();
+
+    assert_eq!(**scene.camera, Camera(3));
+    assert_eq!(**scene.audio, Audio);
+    assert!(Arc::ptr_eq(&scene.channel, &channel));
+    assert_eq!(scene.untouched, 0);
+    assert!(!icebox.contains::<Camera>());
+    assert!(!icebox.contains::<Audio>());
+}
+
+#[test]
+fn every_missing_dependency_is_reported_at_once() {
+    let mut icebox = icebox_with_camera();
+    let edges = Edges::new();
+
+    let mut scene = Injected::default();
+    let Err(InjectError::Missing {
+        scene: name,
+        missing,
+    }) = scene.inject(&mut icebox, &edges)
+    else {
+        panic!("Expected missing dependencies.");
+    };
+
+    assert!(name.ends_with("Injected"));
+    assert_eq!(
+        missing,
+        [
+            MissingDependency {
+                field: "audio",
+                source: DependencySource::IceBox,
+                type_name: std::any::type_name::<Audio>(),
+            },
+            MissingDependency {
+                field: "channel",
+                source: DependencySource::Edge,
+                type_name: std::any::type_name::<EventChannel<u32>>(),
+            },
+        ]
+    );
+    //  Nothing is taken when any dependency is missing.
+    assert!(icebox.contains::<Camera>());
+    assert!(!scene.camera.is_initialized());
+}
+
+#[test]
+fn missing_dependencies_are_listed_in_the_error() {
+    let mut scene = Injected::default();
+    let err = scene
+        .inject(&mut IceBox::new(), &Edges::new())
+        .unwrap_err()
+        .to_string();
+
+    assert!(err.contains("`camera` needs"));
+    assert!(err.contains("`audio` needs"));
+    assert!(err.contains("`channel` needs a producer of edge"));
+}
+
+#[test]
+fn injecting_twice_is_an_error() {
+    let mut icebox = icebox_with_camera();
+    icebox.put(Box::new(Audio));
+    let mut edges = Edges::new();
+    edges.publish(EventChannel::<u32>::default()).unwrap();
+
+    let mut scene = Injected::default();
+    scene.inject(&mut icebox, &edges).unwrap();
+
+    let mut icebox = icebox_with_camera();
+    icebox.put(Box::new(Audio));
+    let Err(InjectError::AlreadyInjected { fields, .. }) = scene.inject(&mut icebox, &edges) else {
+        panic!("Expected the fields to be already injected.");
+    };
+    assert_eq!(fields, ["camera", "audio", "channel"]);
+    //  Nothing is taken from the icebox.
+    assert!(icebox.contains::<Camera>());
+    assert!(icebox.contains::<Audio>());
+}
+
+#[test]
+fn one_icebox_value_cannot_fill_two_fields() {
+    let mut icebox = icebox_with_camera();
+    let mut scene = Duplicated::default();
+    let Err(InjectError::Missing { missing, .. }) = scene.inject(&mut icebox, &Edges::new()) else {
+        panic!("Expected the second field to be missing.");
+    };
+
+    assert_eq!(missing.len(), 1);
+    assert_eq!(missing[0].field, "second");
+    assert!(icebox.contains::<Camera>());
+}
+
+#[test]
+fn scenes_without_dependencies_derive_an_empty_inject() {
+    let mut scene = Plain;
+    let mut icebox = icebox_with_camera();
+    scene.inject(&mut icebox, &Edges::new()).unwrap();
+    scene.inject(&mut icebox, &Edges::new()).unwrap();
+    assert!(icebox.contains::<Camera>());
+    assert!(matches!(
+        scene.run(IceBox::new(), Frames::new(Edges::new())),
+        Ok(SceneExit::End)
+    ));
+}
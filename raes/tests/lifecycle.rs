@@ -0,0 +1,84 @@
+use raes::base::*;
+use std::sync::{Arc, Mutex};
+
+struct Small;
+
+impl Cont for Small {}
+
+fn buffered(capacity: usize, overflow: Overflow) -> ContEntities<Small, 8> {
+    let mut entities = ContEntities::new();
+    entities.buffer_lifecycle(EventBufferConfig { capacity, overflow });
+    entities
+}
+
+#[test]
+fn observers_see_spawns_and_despawns_as_they_happen() {
+    let mut entities = ContEntities::<Small, 8>::new();
+    let spawned = Arc::new(Mutex::new(vec![]));
+    let despawned = Arc::new(Mutex::new(vec![]));
+    entities.on_spawn({
+        let spawned = spawned.clone();
+        move |entity| spawned.lock().unwrap().push(entity)
+    });
+    entities.on_despawn({
+        let despawned = despawned.clone();
+        move |entity| despawned.lock().unwrap().push(entity)
+    });
+
+    let a = entities.spawn();
+    let b = entities.spawn();
+    entities.despawn(a).unwrap();
+    assert_eq!(entities.despawn(a), None);
+
+    assert_eq!(*spawned.lock().unwrap(), vec![a, b]);
+    assert_eq!(*despawned.lock().unwrap(), vec![a]);
+}
+
+#[test]
+fn lifecycle_is_not_buffered_by_default() {
+    let mut entities = ContEntities::<Small, 8>::new();
+    entities.spawn();
+    assert!(entities.spawned().is_none());
+    assert!(entities.despawned().is_none());
+}
+
+#[test]
+fn buffers_hold_entities_until_flush() {
+    let mut entities = buffered(4, Overflow::DropNewest);
+    let a = entities.spawn();
+    let b = entities.spawn();
+    entities.despawn(b).unwrap();
+
+    let spawned = entities.spawned().unwrap().iter().copied();
+    assert_eq!(spawned.collect::<Vec<_>>(), vec![a, b]);
+    let despawned = entities.despawned().unwrap().iter().copied();
+    assert_eq!(despawned.collect::<Vec<_>>(), vec![b]);
+
+    entities.flush();
+    assert!(entities.spawned().unwrap().is_empty());
+    assert!(entities.despawned().unwrap().is_empty());
+}
+
+#[test]
+fn buffers_stay_bounded_without_flush() {
+    let mut entities = buffered(2, Overflow::DropOldest);
+    let calls = Arc::new(Mutex::new(0));
+    entities.on_spawn({
+        let calls = calls.clone();
+        move |_| *calls.lock().unwrap() += 1
+    });
+
+    let all = (0..5)
+        .map(|_| {
+            let entity = entities.spawn();
+            entities.despawn(entity).unwrap();
+            entity
+        })
+        .collect::<Vec<_>>();
+
+    let spawned = entities.spawned().unwrap();
+    assert_eq!(spawned.iter().copied().collect::<Vec<_>>(), all[3..]);
+    assert_eq!(spawned.dropped(), 3);
+    assert_eq!(entities.despawned().unwrap().len(), 2);
+    assert_eq!(*calls.lock().unwrap(), 5);
+}
@@ -0,0 +1,238 @@
+use raes::{asset::*, base::*};
+use std::{fs, time::Duration};
+
+#[derive(Cont, ContPrefab)]
+struct Enemies {
+    entities: ContEntities<Self, 8>,
+    health: Row<f32, 8>,
+    name: Row<String, 8>,
+    shield: SparseRow<u32>,
+    boss: TagRow,
+}
+
+impl Enemies {
+    fn new() -> Self {
+        Self {
+            entities: ContEntities::new(),
+            health: Row::new(1.0),
+            name: Row::new(String::new()),
+            shield: SparseRow::new(),
+            boss: TagRow::new(),
+        }
+    }
+}
+
+const GOBLIN: &str = r#"
+Prefab(
+    rows: {
+        "health": 30.0,
+        "name": "goblin",
+        "boss": true,
+    },
+    children: [
+        Prefab(
+            rows: { "shield": 4 },
+            children: [Prefab(rows: { "health": 5.0 })],
+        ),
+        Prefab(),
+    ],
+)
+"#;
+
+fn prefab(ron: &str) -> Prefab {
+    Prefab::from_ron(ron.as_bytes()).unwrap()
+}
+
+#[test]
+fn from_ron_reads_rows_and_children() {
+    let goblin = prefab(GOBLIN);
+    assert_eq!(goblin.rows.len(), 3);
+    assert_eq!(
+        goblin.rows["name"],
+        PrefabValue::String("goblin".to_owned())
+    );
+    assert_eq!(goblin.children.len(), 2);
+    assert_eq!(goblin.children[0].children.len(), 1);
+    assert_eq!(goblin.children[1], Prefab::default());
+
+    assert!(matches!(
+        Prefab::from_ron(b"Prefab(rows: {"),
+        Err(PrefabError::Parse(_))
+    ));
+}
+
+#[test]
+fn spawn_writes_rows_and_parents_nested_children() {
+    let mut enemies = Enemies::new();
+    let mut hierarchy = Hierarchy::new();
+    let root = prefab(GOBLIN).spawn(&mut enemies, &mut hierarchy).unwrap();
+
+    assert_eq!(*enemies.health(root), 30.0);
+    assert_eq!(enemies.name(root), "goblin");
+    assert!(enemies.boss.test(root));
+
+    let children = hierarchy.children(root).to_vec();
+    assert_eq!(children.len(), 2);
+    assert_eq!(enemies.shield.get(children[0]), Some(&4));
+    assert_eq!(*enemies.health(children[1]), 1.0);
+    assert!(!enemies.boss.test(children[0]));
+
+    let grandchildren = hierarchy.children(children[0]);
+    assert_eq!(grandchildren.len(), 1);
+    assert_eq!(*enemies.health(grandchildren[0]), 5.0);
+    assert_eq!(hierarchy.parent(grandchildren[0]), Some(children[0]));
+    assert_eq!(enemies.entities.iter().count(), 4);
+}
+
+fn assert_rolled_back(enemies: &mut Enemies, hierarchy: &Hierarchy<Enemies>) {
+    assert_eq!(enemies.entities.iter().count(), 0);
+    assert!(enemies.shield.is_empty());
+    assert_eq!(enemies.boss.count(), 0);
+
+    //  Every slot is clean again for the next spawn.
+    for _ in 0..4 {
+        let enemy = enemies.spawn_empty();
+        assert_eq!(*enemies.health(enemy), 1.0);
+        assert_eq!(enemies.name(enemy), "");
+        assert!(hierarchy.parent(enemy).is_none());
+        assert!(hierarchy.children(enemy).is_empty());
+    }
+}
+
+#[test]
+fn unknown_rows_roll_back_the_whole_tree() {
+    let mut enemies = Enemies::new();
+    let mut hierarchy = Hierarchy::new();
+    let broken = prefab(
+        r#"Prefab(
+            rows: { "health": 2.0, "boss": true },
+            children: [Prefab(rows: { "shield": 1 }), Prefab(rows: { "mana": 3 })],
+        )"#,
+    );
+
+    let result = broken.spawn(&mut enemies, &mut hierarchy);
+    assert_eq!(
+        result.err(),
+        Some(PrefabError::UnknownRow("mana".to_owned()))
+    );
+    assert_rolled_back(&mut enemies, &hierarchy);
+}
+
+#[test]
+fn bad_values_roll_back_the_whole_tree() {
+    let mut enemies = Enemies::new();
+    let mut hierarchy = Hierarchy::new();
+    let broken = prefab(
+        r#"Prefab(
+            rows: { "name": "orc" },
+            children: [Prefab(rows: { "health": "lots" })],
+        )"#,
+    );
+
+    let result = broken.spawn(&mut enemies, &mut hierarchy);
+    assert!(matches!(result, Err(PrefabError::BadValue { row, .. }) if row == "health"));
+    assert_rolled_back(&mut enemies, &hierarchy);
+}
+
+#[test]
+fn failed_overrides_roll_back_the_whole_tree() {
+    let mut enemies = Enemies::new();
+    let mut hierarchy = Hierarchy::new();
+    let result = prefab(GOBLIN).spawn_with(&mut enemies, &mut hierarchy, |enemies, goblin| {
+        enemies.write_prefab_row(goblin, "boss", &PrefabValue::Number(1.into()))
+    });
+
+    assert!(matches!(result, Err(PrefabError::BadValue { row, .. }) if row == "boss"));
+    assert_rolled_back(&mut enemies, &hierarchy);
+}
+
+#[test]
+fn full_containers_roll_back_the_whole_tree() {
+    let mut enemies = Enemies::new();
+    let mut hierarchy = Hierarchy::new();
+    let goblin = prefab(GOBLIN);
+    goblin.spawn(&mut enemies, &mut hierarchy).unwrap();
+    let fillers = (0..3).map(|_| enemies.spawn_empty()).collect::<Vec<_>>();
+
+    //  Only the root of the second goblin fits.
+    let result = goblin.spawn(&mut enemies, &mut hierarchy);
+    assert!(matches!(
+        result,
+        Err(PrefabError::Cont(ContError::CapacityExceeded {
+            max: 8,
+            ..
+        }))
+    ));
+    assert_eq!(enemies.entities.iter().count(), 7);
+    assert_eq!(enemies.boss.count(), 1);
+
+    for filler in fillers {
+        enemies.despawn(filler).unwrap();
+    }
+    assert!(goblin.spawn(&mut enemies, &mut hierarchy).is_ok());
+}
+
+#[test]
+fn spawn_empty_resets_slots_written_through_stale_handles() {
+    let mut enemies = Enemies::new();
+    let old = enemies.spawn_with(2.0, "old".to_owned());
+    enemies.despawn(old).unwrap();
+
+    //  Rows and tags do not check generations, so stale handles can still write to the slot.
+    *enemies.health_mut(old) = 9.0;
+    *enemies.name_mut(old) = "stale".to_owned();
+    enemies.shield.insert(old, 3);
+    enemies.boss.set(old);
+
+    let new = enemies.spawn_empty();
+    assert_eq!(new.id(), old.id());
+    assert_eq!(*enemies.health(new), 1.0);
+    assert_eq!(enemies.name(new), "");
+    assert!(enemies.shield.is_empty());
+    assert!(!enemies.boss.test(new));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn prefab_asset_reloads_on_file_changes() {
+    //  The asset watcher watches the working directory, which is the package root under test.
+    let path = format!("prefab_reload_{}.ron", std::process::id());
+    fs::write(&path, "Prefab(rows: { \"health\": 1.0 })").unwrap();
+
+    let watched = path.clone();
+    let result = async move {
+        let path = watched;
+        let loader = AssetLoaderEdgeData::new();
+        let mut asset = PrefabAsset::load(&loader, &path).await.unwrap();
+        assert_eq!(asset.get().rows["health"], PrefabValue::Number(1.0.into()));
+        assert_eq!(asset.reload(), Ok(false));
+
+        //  Give the watcher time to start before changing the file.
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        fs::write(&path, "Prefab(rows: { \"health\": 2.0 })").unwrap();
+        //  The watcher may also see the file while it is truncated, which fails to parse.
+        let health = PrefabValue::Number(2.0.into());
+        for _ in 0..100 {
+            if asset.reload() == Ok(true) && asset.get().rows["health"] == health {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        assert_eq!(asset.get().rows["health"], health);
+
+        //  A broken file keeps the previous prefab.
+        fs::write(&path, "Prefab(rows: {").unwrap();
+        let mut failed = false;
+        for _ in 0..100 {
+            if let Err(PrefabError::Parse(_)) = asset.reload() {
+                failed = true;
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        assert!(failed);
+        assert_eq!(asset.get().rows["health"], PrefabValue::Number(2.0.into()));
+    };
+    let result = tokio::spawn(result).await;
+    fs::remove_file(&path).unwrap();
+    result.unwrap();
+}
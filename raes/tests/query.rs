@@ -0,0 +1,247 @@
+use raes::base::*;
+
+struct Ships;
+impl Cont for Ships {}
+
+#[test]
+fn query_joins_shared_and_mutable_rows() {
+    let mut entities = ContEntities::<Ships, 8>::new();
+    let mut positions = Row::<i32, 8>::new(0);
+    let mut velocities = Row::<i32, 8>::new(0);
+    let ships = (0..4).map(|_| entities.spawn()).collect::<Vec<_>>();
+    for (i, &ship) in ships.iter().enumerate() {
+        *positions.get_mut(ship) = i as i32 * 10;
+        *velocities.get_mut(ship) = i as i32;
+    }
+
+    let mut visited = vec![];
+    for (ship, velocity, mut position) in entities.query((&velocities, &mut positions)) {
+        *position += *velocity;
+        visited.push(ship);
+    }
+
+    assert_eq!(visited, ships);
+    for (i, &ship) in ships.iter().enumerate() {
+        assert_eq!(*positions.get(ship), i as i32 * 11);
+        assert_eq!(*velocities.get(ship), i as i32);
+    }
+}
+
+#[test]
+fn query_skips_dead_entities() {
+    let mut entities = ContEntities::<Ships, 8>::new();
+    let mut health = Row::<u32, 8>::new(10);
+    let ships = (0..5).map(|_| entities.spawn()).collect::<Vec<_>>();
+    entities.despawn(ships[1]).unwrap();
+    entities.despawn(ships[3]).unwrap();
+
+    for (_, mut health) in entities.query((&mut health,)) {
+        *health -= 1;
+    }
+
+    let alive = entities
+        .query((&health,))
+        .map(|(ship, _)| ship)
+        .collect::<Vec<_>>();
+    assert_eq!(alive, vec![ships[0], ships[2], ships[4]]);
+    assert_eq!(*health.get(ships[1]), 10);
+    assert_eq!(*health.get(ships[3]), 10);
+    assert_eq!(*health.get(ships[4]), 9);
+}
+
+#[test]
+fn query_reads_unwritten_growable_rows() {
+    let mut entities = ContEntities::<Ships, GROWABLE>::new();
+    let mut fuel = Row::<u32, GROWABLE>::new(3);
+    let mut cargo = Row::<u32, GROWABLE>::new(0);
+    let ships = (0..150).map(|_| entities.spawn()).collect::<Vec<_>>();
+    *cargo.get_mut(ships[0]) = 1;
+
+    assert_eq!(
+        entities
+            .query((&fuel, &cargo))
+            .map(|(_, fuel, _)| *fuel)
+            .sum::<u32>(),
+        450
+    );
+
+    for (_, mut fuel, cargo) in entities.query((&mut fuel, &cargo)) {
+        *fuel += *cargo;
+    }
+    assert_eq!(*fuel.get(ships[0]), 4);
+    assert_eq!(*fuel.get(ships[149]), 3);
+    assert_eq!(fuel.as_slice().len(), 150);
+}
+
+#[test]
+fn query_skips_entities_without_sparse_value() {
+    let mut entities = ContEntities::<Ships, 8>::new();
+    let mut speed = Row::<u32, 8>::new(1);
+    let mut boost = SparseRow::<u32>::new();
+    let ships = (0..4).map(|_| entities.spawn()).collect::<Vec<_>>();
+    boost.insert(ships[1], 5);
+    boost.insert(ships[3], 7);
+
+    for (_, boost, mut speed) in entities.query((&boost, &mut speed)) {
+        *speed += *boost;
+    }
+    assert_eq!(
+        ships
+            .iter()
+            .map(|&ship| *speed.get(ship))
+            .collect::<Vec<_>>(),
+        vec![1, 6, 1, 8]
+    );
+
+    for (_, boost) in entities.query((&mut boost,)) {
+        *boost *= 2;
+    }
+    assert_eq!(boost.get(ships[1]), Some(&10));
+    assert_eq!(boost.get(ships[3]), Some(&14));
+}
+
+#[test]
+fn query_ignores_sparse_values_of_recycled_slots() {
+    let mut entities = ContEntities::<Ships, 1>::new();
+    let mut boost = SparseRow::<u32>::new();
+    let old = entities.spawn();
+    boost.insert(old, 5);
+    entities.despawn(old).unwrap();
+    let new = entities.spawn();
+    assert_eq!(new.id(), old.id());
+
+    assert_eq!(entities.query((&boost,)).count(), 0);
+    assert_eq!(entities.query((&mut boost,)).count(), 0);
+    assert!(boost.get(new).is_none());
+}
+
+#[test]
+fn par_query_visits_every_live_entity_once() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    let mut entities = ContEntities::<Ships, GROWABLE>::new();
+    let mut visits = Row::<u32, GROWABLE>::new(0);
+    let speed = Row::<u32, GROWABLE>::new(2);
+    let ships = (0..1000).map(|_| entities.spawn()).collect::<Vec<_>>();
+    for ship in ships.iter().step_by(3) {
+        entities.despawn(*ship).unwrap();
+    }
+
+    let count = AtomicUsize::new(0);
+    entities.par_query((&speed, &mut visits), |(_, speed, mut visits)| {
+        *visits += *speed;
+        count.fetch_add(1, Ordering::Relaxed);
+    });
+
+    assert_eq!(count.into_inner(), entities.iter().count());
+    for (i, &ship) in ships.iter().enumerate() {
+        let expected = if i % 3 == 0 { 0 } else { 2 };
+        assert_eq!(*visits.get(ship), expected);
+    }
+}
+
+#[test]
+fn par_for_each_mut_and_par_for_each() {
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    let mut entities = ContEntities::<Ships, 256>::new();
+    let mut ids = Row::<u64, 256>::new(0);
+    for _ in 0..200 {
+        entities.spawn();
+    }
+
+    ids.par_for_each_mut(&entities, |ship, id| *id = ship.id() as u64);
+
+    let sum = AtomicU64::new(0);
+    ids.par_for_each(&entities, |ship, id| {
+        assert_eq!(*id, ship.id() as u64);
+        sum.fetch_add(*id, Ordering::Relaxed);
+    });
+    assert_eq!(sum.into_inner(), (0..200).sum::<u64>());
+}
+
+/// 200 ships, with `visible` set around every 64-bit word boundary and `frozen` on every third.
+fn tagged_fleet() -> (ContEntities<Ships, 256>, Row<u32, 256>, TagRow, TagRow) {
+    let mut entities = ContEntities::new();
+    let mut ids = Row::new(0);
+    let mut visible = TagRow::new();
+    let mut frozen = TagRow::new();
+    for id in 0..200 {
+        let ship = entities.spawn();
+        *ids.get_mut(ship) = id;
+        if matches!(id, 0 | 62 | 63 | 64 | 65 | 190 | 191 | 192 | 199) {
+            visible.set(ship);
+        }
+        if id % 3 == 0 {
+            frozen.set(ship);
+        }
+    }
+    (entities, ids, visible, frozen)
+}
+
+fn expected(entities: &ContEntities<Ships, 256>, pass: impl Fn(Entity<Ships>) -> bool) -> Vec<u32> {
+    entities
+        .iter()
+        .filter(|&ship| pass(ship))
+        .map(|ship| ship.id() as u32)
+        .collect()
+}
+
+#[test]
+fn with_filter_crosses_word_boundaries() {
+    let (entities, ids, visible, _) = tagged_fleet();
+    let visited = entities
+        .query_filtered((&ids,), With(&visible))
+        .map(|(_, id)| *id)
+        .collect::<Vec<_>>();
+
+    assert_eq!(visited, vec![0, 62, 63, 64, 65, 190, 191, 192, 199]);
+    assert_eq!(visited, expected(&entities, |ship| visible.test(ship)));
+}
+
+#[test]
+fn without_filter_crosses_word_boundaries() {
+    let (entities, ids, visible, _) = tagged_fleet();
+    let visited = entities
+        .query_filtered((&ids,), Without(&visible))
+        .map(|(_, id)| *id)
+        .collect::<Vec<_>>();
+
+    assert_eq!(visited.len(), 191);
+    assert_eq!(visited, expected(&entities, |ship| !visible.test(ship)));
+}
+
+#[test]
+fn combined_filters_skip_dead_entities_and_empty_words() {
+    let (mut entities, ids, visible, frozen) = tagged_fleet();
+    for id in [63, 64] {
+        let ship = entities.iter().find(|ship| ship.id() == id).unwrap();
+        entities.despawn(ship).unwrap();
+    }
+
+    let visited = entities
+        .query_filtered((&ids,), (With(&visible), Without(&frozen)))
+        .map(|(_, id)| *id)
+        .collect::<Vec<_>>();
+
+    assert_eq!(visited, vec![62, 65, 190, 191, 199]);
+    assert_eq!(
+        visited,
+        expected(&entities, |ship| visible.test(ship) && !frozen.test(ship))
+    );
+}
+
+#[test]
+fn with_filter_on_a_short_tag_row_ends_the_query() {
+    let mut entities = ContEntities::<Ships, 256>::new();
+    let ids = Row::<u32, 256>::new(0);
+    let mut visible = TagRow::new();
+    let ships = (0..150).map(|_| entities.spawn()).collect::<Vec<_>>();
+    visible.set(ships[5]);
+
+    let visited = entities
+        .query_filtered((&ids,), With(&visible))
+        .map(|(ship, _)| ship)
+        .collect::<Vec<_>>();
+    assert_eq!(visited, vec![ships[5]]);
+}
@@ -0,0 +1,154 @@
+use raes::base::*;
+
+#[derive(Cont)]
+#[cont(snapshot)]
+struct Units {
+    entities: ContEntities<Self, 8>,
+    health: Row<i32, 8>,
+    shield: SparseRow<i32>,
+    selected: TagRow,
+}
+
+impl Units {
+    fn new() -> Self {
+        Self {
+            entities: ContEntities::new(),
+            health: Row::new(10),
+            shield: SparseRow::new(),
+            selected: TagRow::new(),
+        }
+    }
+}
+
+/// Not `Clone`, so a container holding it can only derive `Cont` without `#[cont(snapshot)]`.
+struct Handle(#[allow(dead_code)] Box<u32>);
+
+#[derive(Cont)]
+#[allow(dead_code)]
+struct Handles {
+    entities: ContEntities<Self, 8>,
+    handles: SparseRow<Handle>,
+}
+
+struct Cells;
+
+impl Cont for Cells {}
+
+#[test]
+fn rollback_overwrites_the_oldest_frame_once_full() {
+    let mut units = Units::new();
+    let unit = units.spawn_with(0);
+    let mut rollback = Rollback::new(3);
+
+    for frame in 0..5 {
+        *units.health_mut(unit) = frame as i32;
+        rollback.save(frame, &units);
+    }
+
+    assert_eq!(rollback.len(), 3);
+    assert_eq!(rollback.oldest_frame(), Some(2));
+    assert_eq!(rollback.latest_frame(), Some(4));
+    assert!(rollback.get(1).is_none());
+    assert!(!rollback.rewind(1, &mut units));
+    assert_eq!(*units.health(unit), 4);
+}
+
+#[test]
+fn rewind_restores_every_row_and_discards_later_frames() {
+    let mut units = Units::new();
+    let kept = units.spawn_with(5);
+    units.shield.insert(kept, 3);
+    let mut rollback = Rollback::new(4);
+    rollback.save(0, &units);
+
+    let added = units.spawn_with(7);
+    *units.health_mut(kept) = 1;
+    units.shield.remove(kept);
+    units.selected.set(added);
+    rollback.save(1, &units);
+    rollback.save(2, &units);
+
+    assert!(rollback.rewind(0, &mut units));
+    assert_eq!(rollback.latest_frame(), Some(0));
+    assert!(units.has_entity(&kept));
+    assert!(!units.has_entity(&added));
+    assert_eq!(*units.health(kept), 5);
+    assert_eq!(units.shield.get(kept), Some(&3));
+    assert!(!units.selected.test(added));
+}
+
+#[test]
+fn rollback_of_zero_capacity_saves_nothing() {
+    let units = Units::new();
+    let mut rollback = Rollback::new(0);
+    rollback.save(0, &units);
+    assert!(rollback.is_empty());
+}
+
+#[test]
+fn container_deltas_cover_every_row() {
+    let mut units = Units::new();
+    let unit = units.spawn_with(10);
+    let mut rollback = Rollback::new(2);
+    rollback.save(0, &units);
+
+    let spawned = units.spawn_with(10);
+    *units.health_mut(unit) = 4;
+    units.shield.insert(spawned, 2);
+    units.selected.set(unit);
+    rollback.save(1, &units);
+
+    let (entities, health, shield, selected) = rollback.delta(0, 1).unwrap();
+    assert_eq!(entities.spawned, vec![spawned]);
+    assert!(entities.despawned.is_empty());
+    assert_eq!(health, vec![(unit.id(), 4)]);
+    assert_eq!(shield, vec![(spawned.id(), 0, Some(2))]);
+    assert_eq!(selected, vec![(0, 1 << unit.id())]);
+    assert!(rollback.delta(0, 2).is_none());
+}
+
+#[test]
+fn row_delta_applied_to_an_older_row_matches_the_newer_one() {
+    let mut row = Row::<i32, 8>::new(0);
+    let mut entities = ContEntities::<Cells, 8>::new();
+    let cells = (0..4).map(|_| entities.spawn()).collect::<Vec<_>>();
+    let older = row.snapshot();
+    *row.get_mut(cells[1]) = 3;
+    *row.get_mut(cells[3]) = 6;
+    let newer = row.snapshot();
+
+    let mut replayed = Row::<i32, 8>::new(0);
+    replayed.restore(&older);
+    replayed.apply_delta(&older.delta(&newer));
+    assert_eq!(replayed.as_slice(), row.as_slice());
+}
+
+#[test]
+fn row_delta_covers_slots_only_in_the_older_snapshot() {
+    let mut row = Row::<i32, GROWABLE>::new(0);
+    let mut entities = ContEntities::<Cells, GROWABLE>::new();
+    let cells = (0..3).map(|_| entities.spawn()).collect::<Vec<_>>();
+    let empty = row.snapshot();
+    *row.get_mut(cells[1]) = 3;
+    *row.get_mut(cells[2]) = 0;
+    let grown = row.snapshot();
+
+    //  Slot 2 holds the initial value either way.
+    assert_eq!(empty.delta(&grown), vec![(1, 3)]);
+    assert_eq!(grown.delta(&empty), vec![(1, 0)]);
+
+    row.apply_delta(&grown.delta(&empty));
+    assert_eq!(row.as_slice(), &[0, 0, 0]);
+}
+
+#[test]
+fn containers_without_snapshot_accept_values_that_are_not_clone() {
+    let mut handles = Handles {
+        entities: ContEntities::new(),
+        handles: SparseRow::new(),
+    };
+    let entity = handles.entities.spawn();
+    handles.handles.insert(entity, Handle(Box::new(1)));
+    handles.despawn(entity).unwrap();
+    assert!(handles.handles.is_empty());
+}
@@ -0,0 +1,164 @@
+use cgmath::Vector2;
+use raes::base::*;
+
+struct Boids;
+
+impl Cont for Boids {}
+
+fn sorted(entities: impl Iterator<Item = Entity<Boids>>) -> Vec<usize> {
+    let mut ids = entities.map(|entity| entity.id()).collect::<Vec<_>>();
+    ids.sort();
+    ids
+}
+
+/// Boids 0 to 4 along the x axis, one unit apart, plus boid 5 far away.
+fn flock() -> (
+    ContEntities<Boids, 16>,
+    Row<Vector2<f32>, 16>,
+    SpatialGrid<Boids>,
+) {
+    let mut entities = ContEntities::new();
+    let mut positions = Row::new(Vector2::new(0.0, 0.0));
+    for x in 0..5 {
+        let boid = entities.spawn();
+        *positions.get_mut(boid) = Vector2::new(x as f32, 0.0);
+    }
+    let far = entities.spawn();
+    *positions.get_mut(far) = Vector2::new(1000.0, -1000.0);
+
+    let mut grid = SpatialGrid::new(2.0);
+    grid.sync(&entities, &positions);
+    (entities, positions, grid)
+}
+
+#[test]
+fn radius_queries_are_inclusive_and_exact() {
+    let (_, _, grid) = flock();
+    assert_eq!(grid.len(), 6);
+    assert_eq!(
+        sorted(grid.query_radius(Vector2::new(2.0, 0.0), 1.0)),
+        [1, 2, 3]
+    );
+    assert_eq!(sorted(grid.query_radius(Vector2::new(2.0, 1.0), 1.0)), [2]);
+    assert_eq!(
+        sorted(grid.query_radius(Vector2::new(2.0, 0.0), -1.0)),
+        [] as [usize; 0]
+    );
+}
+
+#[test]
+fn unbounded_queries_visit_the_occupied_cells_only() {
+    let (_, _, grid) = flock();
+    let origin = Vector2::new(0.0, 0.0);
+    assert_eq!(
+        sorted(grid.query_radius(origin, f32::INFINITY)),
+        [0, 1, 2, 3, 4, 5]
+    );
+    assert_eq!(sorted(grid.query_radius(origin, 1.0e9)), [0, 1, 2, 3, 4, 5]);
+
+    let everywhere = Vector2::new(f32::INFINITY, f32::INFINITY);
+    assert_eq!(
+        sorted(grid.query_aabb(-everywhere, everywhere)),
+        [0, 1, 2, 3, 4, 5]
+    );
+}
+
+#[test]
+fn aabb_queries_are_inclusive() {
+    let (_, _, grid) = flock();
+    let found = grid.query_aabb(Vector2::new(1.0, -0.5), Vector2::new(3.0, 0.5));
+    assert_eq!(sorted(found), [1, 2, 3]);
+    let found = grid.query_aabb(Vector2::new(999.0, -1001.0), Vector2::new(1001.0, 1.0));
+    assert_eq!(sorted(found), [5]);
+}
+
+#[test]
+fn nearest_finds_the_closest_entity_across_cells() {
+    let (entities, _, grid) = flock();
+    let ids = entities.iter().collect::<Vec<_>>();
+    assert_eq!(grid.nearest(Vector2::new(3.9, 0.3)), Some(ids[4]));
+    assert_eq!(grid.nearest(Vector2::new(-50.0, 0.0)), Some(ids[0]));
+    assert_eq!(grid.nearest(Vector2::new(900.0, -900.0)), Some(ids[5]));
+    assert_eq!(
+        SpatialGrid::<Boids>::new(1.0).nearest(Vector2::new(0.0, 0.0)),
+        None
+    );
+}
+
+#[test]
+fn nearest_stays_cheap_and_in_range_for_far_entities() {
+    let mut entities = ContEntities::<Boids, 16>::new();
+    let mut grid = SpatialGrid::new(1.0);
+    let far = entities.spawn();
+    grid.insert(far, Vector2::new(20000.0, 0.0));
+    assert_eq!(grid.nearest(Vector2::new(0.0, 0.0)), Some(far));
+
+    //  Cells of both positions saturate at the edges of the grid.
+    let edge = entities.spawn();
+    grid.insert(edge, Vector2::new(-1.0e30, 0.0));
+    assert_eq!(grid.nearest(Vector2::new(1.0e30, 0.0)), Some(far));
+    assert_eq!(grid.nearest(Vector2::new(-1.0e30, 5.0)), Some(edge));
+}
+
+#[test]
+fn sync_follows_moves_despawns_and_recycled_slots() {
+    let (mut entities, mut positions, mut grid) = flock();
+    let ids = entities.iter().collect::<Vec<_>>();
+    *positions.get_mut(ids[0]) = Vector2::new(50.0, 50.0);
+    entities.despawn(ids[1]).unwrap();
+    let recycled = entities.spawn();
+    *positions.get_mut(recycled) = Vector2::new(-20.0, 0.0);
+    grid.sync(&entities, &positions);
+
+    assert_eq!(grid.len(), 6);
+    assert!(!grid.contains(ids[1]));
+    assert_eq!(grid.position(recycled), Some(Vector2::new(-20.0, 0.0)));
+    assert_eq!(grid.nearest(Vector2::new(49.0, 49.0)), Some(ids[0]));
+}
+
+#[test]
+fn sync_with_lifecycle_buffers_and_change_tracking() {
+    let mut entities = ContEntities::<Boids, 16>::new();
+    entities.buffer_lifecycle(EventBufferConfig {
+        capacity: 4,
+        overflow: Overflow::DropOldest,
+    });
+    let mut positions = Row::<_, 16>::new(Vector2::new(0.0, 0.0)).with_change_tracking();
+    let mut grid = SpatialGrid::new(1.0);
+
+    let boids = (0..3).map(|_| entities.spawn()).collect::<Vec<_>>();
+    for (x, &boid) in boids.iter().enumerate() {
+        *positions.get_mut(boid) = Vector2::new(x as f32, 0.0);
+    }
+    grid.sync(&entities, &positions);
+    entities.flush();
+    positions.flush();
+    assert_eq!(grid.len(), 3);
+
+    entities.despawn(boids[0]).unwrap();
+    let added = entities.spawn();
+    *positions.get_mut(boids[2]) = Vector2::new(9.0, 9.0);
+    grid.sync(&entities, &positions);
+    entities.flush();
+    positions.flush();
+
+    assert_eq!(grid.len(), 3);
+    assert!(!grid.contains(boids[0]));
+    assert_eq!(grid.position(added), Some(Vector2::new(0.0, 0.0)));
+    assert_eq!(grid.position(boids[2]), Some(Vector2::new(9.0, 9.0)));
+
+    //  More despawns than the buffer holds fall back to checking every indexed entity.
+    for &boid in &boids[1..] {
+        entities.despawn(boid).unwrap();
+    }
+    for _ in 0..3 {
+        let boid = entities.spawn();
+        entities.despawn(boid).unwrap();
+    }
+    grid.sync(&entities, &positions);
+    assert!(entities.despawned().unwrap().dropped() > 0);
+    assert_eq!(
+        sorted(grid.query_radius(Vector2::new(0.0, 0.0), f32::INFINITY)),
+        [added.id()]
+    );
+}
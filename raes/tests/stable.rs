@@ -0,0 +1,74 @@
+use raes::base::*;
+
+struct Players;
+impl Cont for Players {}
+
+#[test]
+fn assign_is_stable_and_never_reuses_ids() {
+    let mut entities = ContEntities::<Players, 4>::new();
+    let mut ids = StableIds::new();
+    let first = entities.spawn();
+    let second = entities.spawn();
+
+    assert_eq!(ids.assign(first), StableId(0));
+    assert_eq!(ids.assign(second), StableId(1));
+    assert_eq!(ids.assign(first), StableId(0));
+    assert_eq!(ids.resolve(StableId(1)), Some(second));
+    assert_eq!(ids.stable_id(first), Some(StableId(0)));
+
+    assert_eq!(ids.remove(first), Some(StableId(0)));
+    assert_eq!(ids.remove(first), None);
+    assert_eq!(ids.resolve(StableId(0)), None);
+    assert_eq!(ids.stable_id(first), None);
+    assert_eq!(ids.assign(first), StableId(2));
+}
+
+#[test]
+fn bind_repoints_id_to_respawned_entity() {
+    let mut entities = ContEntities::<Players, 4>::new();
+    let mut ids = StableIds::new();
+    let old = entities.spawn();
+    let stable = ids.assign(old);
+    entities.despawn(old).unwrap();
+    let respawned = entities.spawn();
+
+    ids.bind(stable, respawned);
+    assert_eq!(ids.resolve(stable), Some(respawned));
+    assert_eq!(ids.stable_id(respawned), Some(stable));
+    assert_eq!(ids.stable_id(old), None);
+}
+
+#[test]
+fn bind_moves_entity_off_its_previous_id() {
+    let mut entities = ContEntities::<Players, 4>::new();
+    let mut ids = StableIds::new();
+    let player = entities.spawn();
+    let previous = ids.assign(player);
+
+    ids.bind(StableId(10), player);
+    assert_eq!(ids.resolve(previous), None);
+    assert_eq!(ids.resolve(StableId(10)), Some(player));
+    assert_eq!(ids.stable_id(player), Some(StableId(10)));
+
+    let other = entities.spawn();
+    assert_eq!(ids.assign(other), StableId(11));
+}
+
+#[test]
+fn bind_max_id_does_not_overflow() {
+    let mut entities = ContEntities::<Players, 4>::new();
+    let mut ids = StableIds::new();
+    let player = entities.spawn();
+    ids.bind(StableId(u64::MAX), player);
+    assert_eq!(ids.resolve(StableId(u64::MAX)), Some(player));
+    assert_eq!(ids.assign(player), StableId(u64::MAX));
+}
+
+#[test]
+#[should_panic(expected = "On assign: every stable id of container")]
+fn assign_after_max_id_panics() {
+    let mut entities = ContEntities::<Players, 4>::new();
+    let mut ids = StableIds::new();
+    ids.bind(StableId(u64::MAX), entities.spawn());
+    ids.assign(entities.spawn());
+}
@@ -0,0 +1,264 @@
+use raes::base::*;
+use std::{
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+    thread,
+    time::{Duration, Instant},
+};
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Frame {
+    number: u64,
+    squares: Vec<u64>,
+    events: Vec<u64>,
+}
+
+impl Frame {
+    fn is_consistent(&self) -> bool {
+        self.squares.len() as u64 == self.number
+            && self
+                .squares
+                .iter()
+                .enumerate()
+                .all(|(i, &square)| square == (i as u64) * (i as u64))
+            && self.events.iter().all(|&event| event == self.number)
+    }
+}
+
+impl Flushable for Frame {
+    fn flush(&mut self) {
+        self.events.clear();
+    }
+}
+
+fn write_frame(frame: &mut Frame) {
+    let number = frame.number;
+    frame.squares.push(number * number);
+    frame.number += 1;
+    frame.events.clear();
+    frame.events.extend(std::iter::repeat_n(frame.number, 4));
+}
+
+#[test]
+fn reads_initial_value() {
+    let buffer = TripleBuffer::new(Frame::default());
+    assert_eq!(*buffer.read(), Frame::default());
+}
+
+#[test]
+fn publish_makes_writes_visible() {
+    let buffer = TripleBuffer::new(Frame::default());
+    {
+        let mut write = buffer.write();
+        write_frame(&mut write);
+        assert_eq!(buffer.read().number, 0);
+        write.publish();
+    }
+    let read = buffer.read();
+    assert_eq!(read.number, 1);
+    assert_eq!(read.events, vec![1; 4]);
+}
+
+#[test]
+fn writer_continues_from_flushed_copy() {
+    let buffer = TripleBuffer::new(Frame::default());
+    let mut write = buffer.write();
+    write_frame(&mut write);
+    write.publish();
+
+    assert_eq!(write.number, 1);
+    assert_eq!(write.squares, vec![0]);
+    assert!(write.events.is_empty());
+}
+
+#[test]
+fn only_latest_publish_is_read() {
+    let buffer = TripleBuffer::new(Frame::default());
+    for _ in 0..5 {
+        let mut write = buffer.write();
+        write_frame(&mut write);
+        write.publish();
+    }
+    assert_eq!(buffer.read().number, 5);
+}
+
+#[test]
+fn overlapping_readers_see_new_publishes() {
+    let buffer = TripleBuffer::new(Frame::default());
+    let first = buffer.read();
+
+    let mut write = buffer.write();
+    write_frame(&mut write);
+    assert!(write.publish());
+    drop(write);
+
+    let second = buffer.read();
+    assert_eq!(first.number, 0);
+    assert_eq!(second.number, 1);
+    assert!(first.is_consistent() && second.is_consistent());
+}
+
+#[test]
+fn publishing_is_deferred_while_guards_hold_the_spare() {
+    let buffer = TripleBuffer::new(Frame::default());
+    let mut write = buffer.write();
+    let oldest = buffer.read();
+    write_frame(&mut write);
+    assert!(write.publish());
+    let older = buffer.read();
+
+    //  Both buffers the writer could continue in are held.
+    write_frame(&mut write);
+    assert!(!write.publish());
+    assert_eq!(buffer.read().number, 1);
+    write_frame(&mut write);
+    assert!(!write.publish());
+
+    drop(oldest);
+    assert!(write.publish());
+    let latest = buffer.read();
+    assert_eq!(latest.number, 3);
+    assert!(latest.is_consistent());
+    assert_eq!(older.number, 1);
+}
+
+#[test]
+fn publish_through_shared_reference() {
+    let buffer = Arc::new(TripleBuffer::new(Frame::default()));
+    let writer = Arc::clone(&buffer);
+    thread::spawn(move || {
+        write_frame(&mut writer.write());
+        writer.publish();
+    })
+    .join()
+    .unwrap();
+    assert_eq!(buffer.read().number, 1);
+}
+
+#[test]
+fn try_write_is_exclusive() {
+    let buffer = TripleBuffer::new(Frame::default());
+    let write = buffer.try_write();
+    assert!(write.is_some());
+    assert!(buffer.try_write().is_none());
+    drop(write);
+    assert!(buffer.try_write().is_some());
+}
+
+#[test]
+#[should_panic(expected = "is already being written.")]
+fn second_writer_panics() {
+    let buffer = TripleBuffer::new(Frame::default());
+    let _write = buffer.write();
+    buffer.publish();
+}
+
+#[test]
+fn stress_readers_see_consistent_monotonic_frames() {
+    const FRAMES: u64 = 20_000;
+    const READERS: usize = 4;
+
+    let buffer = Arc::new(TripleBuffer::new(Frame::default()));
+    let done = Arc::new(AtomicBool::new(false));
+
+    let readers = (0..READERS)
+        .map(|_| {
+            let buffer = Arc::clone(&buffer);
+            let done = Arc::clone(&done);
+            thread::spawn(move || {
+                let mut last = 0;
+                let mut reads = 0;
+                while !done.load(Ordering::Acquire) {
+                    let frame = buffer.read();
+                    assert!(frame.is_consistent(), "torn read of frame {}", frame.number);
+                    assert!(frame.number >= last, "went back in time");
+                    last = frame.number;
+                    reads += 1;
+                }
+                (last, reads)
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let writer = {
+        let buffer = Arc::clone(&buffer);
+        thread::spawn(move || {
+            let mut write = buffer.write();
+            for _ in 0..FRAMES {
+                write_frame(&mut write);
+                write.publish();
+            }
+            //  Retry the last frame until no reader holds the spare.
+            while !write.publish() {
+                std::hint::spin_loop();
+            }
+        })
+    };
+
+    writer.join().unwrap();
+    done.store(true, Ordering::Release);
+    for reader in readers {
+        let (_, reads) = reader.join().unwrap();
+        assert!(reads > 0);
+    }
+
+    let frame = buffer.read();
+    assert_eq!(frame.number, FRAMES);
+    assert!(frame.is_consistent());
+}
+
+#[test]
+fn stress_overlapping_readers_keep_seeing_new_frames() {
+    const FRAMES: u64 = 5_000;
+    const READERS: usize = 4;
+
+    let buffer = Arc::new(TripleBuffer::new(Frame::default()));
+    let done = Arc::new(AtomicBool::new(false));
+    let caught_up = Arc::new(AtomicUsize::new(0));
+
+    //  Every reader always holds a guard, so readers never all let go at once.
+    let readers = (0..READERS)
+        .map(|_| {
+            let buffer = Arc::clone(&buffer);
+            let done = Arc::clone(&done);
+            let caught_up = Arc::clone(&caught_up);
+            thread::spawn(move || {
+                let mut held = buffer.read();
+                while !done.load(Ordering::Acquire) {
+                    let next = buffer.read();
+                    assert!(next.is_consistent(), "torn read of frame {}", next.number);
+                    assert!(next.number >= held.number, "went back in time");
+                    if next.number == FRAMES && held.number != FRAMES {
+                        caught_up.fetch_add(1, Ordering::AcqRel);
+                    }
+                    held = next;
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let writer = {
+        let buffer = Arc::clone(&buffer);
+        thread::spawn(move || {
+            let mut write = buffer.write();
+            for _ in 0..FRAMES {
+                write_frame(&mut write);
+                write.publish();
+            }
+            while !write.publish() {
+                std::hint::spin_loop();
+            }
+        })
+    };
+    writer.join().unwrap();
+
+    //  Readers must pick up the last frame while still overlapping.
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while caught_up.load(Ordering::Acquire) != READERS {
+        assert!(Instant::now() < deadline, "overlapping readers starved");
+        thread::yield_now();
+    }
+    done.store(true, Ordering::Release);
+    for reader in readers {
+        reader.join().unwrap();
+    }
+}
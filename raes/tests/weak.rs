@@ -0,0 +1,46 @@
+use raes::base::*;
+
+struct Targets;
+
+impl Cont for Targets {}
+
+#[test]
+fn resolve_follows_a_live_target() {
+    let mut entities = ContEntities::<Targets, 4>::new();
+    let target = entities.spawn();
+    let weak = WeakEntity::new(target);
+
+    assert_eq!(weak.resolve(&entities), Some(target));
+    assert!(!weak.is_dangling(&entities));
+}
+
+#[test]
+fn recycled_targets_are_not_resolved() {
+    let mut entities = ContEntities::<Targets, 4>::new();
+    let target = entities.spawn();
+    let weak = WeakEntity::from(target);
+
+    entities.despawn(target).unwrap();
+    assert_eq!(weak.resolve(&entities), None);
+    assert!(weak.is_dangling(&entities));
+
+    let recycled = entities.spawn();
+    assert_eq!(recycled.id(), target.id());
+    assert_eq!(weak.resolve(&entities), None);
+    assert!(weak.is_dangling(&entities));
+}
+
+#[test]
+fn empty_references_are_not_dangling() {
+    let mut entities = ContEntities::<Targets, 4>::new();
+    let mut weak = WeakEntity::none();
+    assert_eq!(weak.resolve(&entities), None);
+    assert!(!weak.is_dangling(&entities));
+
+    let target = entities.spawn();
+    weak.set(target);
+    entities.despawn(target).unwrap();
+    weak.clear();
+    assert!(!weak.is_dangling(&entities));
+    assert_eq!(weak, WeakEntity::default());
+}